@@ -0,0 +1,335 @@
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use ink::storage::Mapping;
+use scale::{
+    Decode,
+    Encode,
+};
+use payload::message_define::{
+    ISQoS,
+    IContent,
+    IRequestMessage,
+};
+use payload::message_protocol::MessagePayload;
+use crate::MultiDestContracts;
+use crate::chain_registry;
+use crate::cross_chain_helper::{
+    self,
+    CrossChainBase,
+    Error,
+};
+
+/// Built-in reducers of query responses.
+#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ReducerKind {
+    /// At least `threshold` responses are the same.
+    Quorum,
+    /// The median of at least `threshold` responses, which are SCALE encoded `u128`.
+    Median,
+    /// At least `threshold` responses are received, and all of them are the same.
+    AllEqual,
+    /// A reducer defined by the application, see `QueryAggregation::reduce`.
+    Custom(u8),
+}
+
+/// Result of reducing the responses of a query group.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Reduced {
+    /// More responses are needed.
+    Pending,
+    /// The responses are reduced to a result.
+    Done(Vec<u8>),
+    /// The responses can not satisfy the reducer any more.
+    Failed,
+}
+
+/// Reduces the responses of a query group to one result.
+pub trait Reducer {
+    /// Reduces `responses`, of which at most `expected` will be received, with `threshold`.
+    fn reduce(& self, responses: &[Vec<u8>], threshold: u32, expected: u32) -> Reduced;
+}
+
+impl Reducer for ReducerKind {
+    fn reduce(& self, responses: &[Vec<u8>], threshold: u32, expected: u32) -> Reduced {
+        let threshold = threshold as usize;
+        let missing = (expected as usize).saturating_sub(responses.len());
+        match self {
+            ReducerKind::Quorum => {
+                let mut best: Option<(&Vec<u8>, usize)> = None;
+                for response in responses {
+                    let count = responses.iter().filter(|r| *r == response).count();
+                    if best.map_or(true, |(_, c)| count > c) {
+                        best = Some((response, count));
+                    }
+                }
+                let (value, count) = match best {
+                    Some(best) => best,
+                    None => return Reduced::Pending,
+                };
+                if count >= threshold {
+                    Reduced::Done(value.clone())
+                }
+                else if count + missing < threshold {
+                    Reduced::Failed
+                }
+                else {
+                    Reduced::Pending
+                }
+            },
+            ReducerKind::Median => {
+                let mut values: Vec<u128> = responses.iter()
+                    .filter_map(|r| u128::decode(&mut r.as_slice()).ok())
+                    .collect();
+                if values.len() >= threshold {
+                    values.sort();
+                    Reduced::Done(values[(values.len() - 1) / 2].encode())
+                }
+                else if values.len() + missing < threshold {
+                    Reduced::Failed
+                }
+                else {
+                    Reduced::Pending
+                }
+            },
+            ReducerKind::AllEqual => {
+                if responses.iter().any(|r| *r != responses[0]) {
+                    Reduced::Failed
+                }
+                else if responses.len() >= threshold {
+                    Reduced::Done(responses[0].clone())
+                }
+                else {
+                    Reduced::Pending
+                }
+            },
+            ReducerKind::Custom(_) => Reduced::Failed,
+        }
+    }
+}
+
+/// A query sent to many chains, of which responses are aggregated.
+#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct QueryGroup {
+    /// Reducer of the responses.
+    pub reducer: ReducerKind,
+    /// Number of responses needed by the reducer.
+    pub threshold: u32,
+    /// Block number after which the group expires.
+    pub deadline: u32,
+    /// Chain names and message ids of the requests.
+    pub requests: Vec<(String, u128)>,
+    /// Responses received.
+    pub responses: Vec<Vec<u8>>,
+}
+
+/// Storage of query groups.
+#[ink::storage_item]
+#[derive(Default)]
+pub struct Aggregator {
+    next_group: u128,
+    groups: Mapping<u128, QueryGroup>,
+    pending: Mapping<(String, u128), u128>,
+}
+
+impl Aggregator {
+    /// Returns the query group `group_id`, if it is not finished.
+    pub fn get_group(& self, group_id: u128) -> Option<QueryGroup> {
+        self.groups.get(group_id)
+    }
+
+    /// Removes the group and its pending requests.
+    fn remove_group(&mut self, group_id: u128, group: &QueryGroup) {
+        for request in group.requests.iter() {
+            self.pending.remove(request);
+        }
+        self.groups.remove(group_id);
+    }
+}
+
+/// If you want to aggregate responses of queries sent to many chains, this trait must be implemented.
+pub trait QueryAggregation: CrossChainBase + MultiDestContracts {
+    /// Returns the query groups of the contract.
+    fn get_aggregator_mut(&mut self) -> &mut Aggregator;
+
+    /// Handles the final result of the group `group_id`.
+    /// `result` is `None` if the responses do not satisfy the reducer, or the group expires.
+    fn on_aggregated(&mut self, group_id: u128, result: Option<Vec<u8>>);
+
+    /// Reduces the responses of `group`.
+    /// Override it to plug in reducers for `ReducerKind::Custom`.
+    fn reduce(& self, group: &QueryGroup) -> Reduced {
+        group.reducer.reduce(&group.responses, group.threshold, group.requests.len() as u32)
+    }
+}
+
+/// Returns the canonical name of every chain in `chains` with its destination contract looked up with `lookup`,
+/// so the requests are recorded with the names which the source chains of their responses are normalized to.
+fn query_dests<T: CrossChainBase>(contract: &T, chains: Vec<String>, lookup: impl Fn(&T, String) -> Option<(Vec<u8>, Vec<u8>)>) -> Result<Vec<(String, (Vec<u8>, Vec<u8>))>, Error> {
+    let mut dests = Vec::new();
+    for chain_name in chains {
+        let chain_name = chain_registry::canonical_chain_name(contract, &chain_name)?;
+        let dest = lookup(contract, chain_name.clone()).ok_or(Error::DestNotRegistered)?;
+        dests.push((chain_name, dest));
+    }
+
+    Ok(dests)
+}
+
+/// Sends `data` to every destination in `dests` with `callback`, and returns the chain name and message id of each request.
+fn send_requests<T: CrossChainBase>(contract: &mut T, dests: Vec<(String, (Vec<u8>, Vec<u8>))>, data: Vec<u8>, callback: [u8; 4]) -> Result<Vec<(String, u128)>, Error> {
+    let mut requests = Vec::new();
+    for (chain_name, (dest_contract, dest_action)) in dests {
+        let content = IContent::new(dest_contract, dest_action, data.clone());
+        let request = IRequestMessage::new(chain_name.clone(), Vec::<ISQoS>::new(), content);
+        let id = cross_chain_helper::cross_chain_call(contract, request, callback)?;
+        requests.push((chain_name, id));
+    }
+
+    Ok(requests)
+}
+
+/// Sends the same query to the destination contracts registered for `action` on every chain in `chains`, with `callback`.
+/// Returns the id of the query group. The group expires `timeout` blocks later.
+pub fn cross_chain_query<T: QueryAggregation>(contract: &mut T, action: String, payload: MessagePayload, chains: Vec<String>, callback: [u8; 4], reducer: ReducerKind, threshold: u32, timeout: u32) -> Result<u128, Error> {
+    if threshold == 0 || threshold as usize > chains.len() {
+        return Err(Error::InvalidThreshold);
+    }
+
+    let dests = query_dests(contract, chains, |contract, chain_name| contract.get_dest_contract_info(chain_name, action.clone()))?;
+    let requests = send_requests(contract, dests, payload.to_bytes(), callback)?;
+
+    let now = ink::env::block_number::<ink::env::DefaultEnvironment>();
+    let aggregator = contract.get_aggregator_mut();
+    let group_id = aggregator.next_group;
+    aggregator.next_group = group_id + 1;
+    for request in requests.iter() {
+        aggregator.pending.insert(request, &group_id);
+    }
+    let group = QueryGroup {
+        reducer,
+        threshold,
+        deadline: now.saturating_add(timeout),
+        requests,
+        responses: Vec::new(),
+    };
+    aggregator.groups.insert(group_id, &group);
+
+    Ok(group_id)
+}
+
+/// Records `response` of a query, it should be called by the callback message of the query.
+/// Once the responses are reduced, or can not satisfy the reducer, `QueryAggregation::on_aggregated` is called.
+/// Responses of groups which are finished or expired are acknowledged and ignored,
+/// so late callbacks are not reverted, and seen as failed deliveries by the cross-chain contract.
+pub fn cross_chain_receive_response<T: QueryAggregation>(contract: &mut T, response: Vec<u8>) -> Result<(), Error> {
+    let context = cross_chain_helper::cross_chain_receive(contract)?;
+    let key = (context.from_chain, context.session.id);
+
+    let aggregator = contract.get_aggregator_mut();
+    let group_id = match aggregator.pending.get(&key) {
+        Some(group_id) => group_id,
+        None => return Ok(()),
+    };
+    aggregator.pending.remove(&key);
+    let mut group = match aggregator.groups.get(group_id) {
+        Some(group) => group,
+        None => return Ok(()),
+    };
+
+    let now = ink::env::block_number::<ink::env::DefaultEnvironment>();
+    let reduced = if now > group.deadline {
+        Reduced::Failed
+    }
+    else {
+        group.responses.push(response);
+        contract.reduce(&group)
+    };
+
+    match reduced {
+        Reduced::Pending => {
+            contract.get_aggregator_mut().groups.insert(group_id, &group);
+        },
+        Reduced::Done(result) => {
+            contract.get_aggregator_mut().remove_group(group_id, &group);
+            contract.on_aggregated(group_id, Some(result));
+        },
+        Reduced::Failed => {
+            contract.get_aggregator_mut().remove_group(group_id, &group);
+            contract.on_aggregated(group_id, None);
+        },
+    }
+
+    Ok(())
+}
+
+/// Finishes the group `group_id` which has expired, `QueryAggregation::on_aggregated` is called with `None`.
+pub fn expire_query<T: QueryAggregation>(contract: &mut T, group_id: u128) -> Result<(), Error> {
+    let aggregator = contract.get_aggregator_mut();
+    let group = aggregator.groups.get(group_id).ok_or(Error::UnknownQuery)?;
+    if ink::env::block_number::<ink::env::DefaultEnvironment>() <= group.deadline {
+        return Err(Error::QueryNotExpired);
+    }
+
+    aggregator.remove_group(group_id, &group);
+    contract.on_aggregated(group_id, None);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use payload::message_define::{
+        IContext,
+        ISession,
+    };
+    use crate::transport::CrossChainTransport;
+    use crate::mock::MockTransport;
+
+    #[derive(Default)]
+    struct MockContract {
+        transport: MockTransport,
+    }
+
+    impl CrossChainBase for MockContract {
+        fn get_transport(& self) -> &dyn CrossChainTransport {
+            &self.transport
+        }
+    }
+
+    /// We test if the built-in reducers wait, succeed and fail as expected.
+    #[test]
+    fn reducers_work() {
+        let one = 1u128.encode();
+        let two = 2u128.encode();
+        let three = 3u128.encode();
+
+        assert_eq!(ReducerKind::Quorum.reduce(&[one.clone(), two.clone()], 2, 3), Reduced::Pending);
+        assert_eq!(ReducerKind::Quorum.reduce(&[one.clone(), two.clone(), one.clone()], 2, 3), Reduced::Done(one.clone()));
+        assert_eq!(ReducerKind::Quorum.reduce(&[one.clone(), two.clone(), three.clone()], 2, 3), Reduced::Failed);
+
+        assert_eq!(ReducerKind::Median.reduce(&[three.clone(), one.clone()], 3, 3), Reduced::Pending);
+        assert_eq!(ReducerKind::Median.reduce(&[three.clone(), one.clone(), two.clone()], 3, 3), Reduced::Done(two.clone()));
+
+        assert_eq!(ReducerKind::AllEqual.reduce(&[one.clone(), one.clone()], 2, 2), Reduced::Done(one.clone()));
+        assert_eq!(ReducerKind::AllEqual.reduce(&[one.clone(), two.clone()], 2, 3), Reduced::Failed);
+    }
+
+    /// We test if queries sent to mixed-case chain names are recorded with the names which their responses come from.
+    #[ink::test]
+    fn mixed_case_chain_names_work() {
+        let mut contract = MockContract::default();
+        let dests = query_dests(&contract, ink::prelude::vec![String::from("Ethereum")], |_, _| Some((Vec::new(), Vec::new()))).unwrap();
+        let requests = send_requests(&mut contract, dests, Vec::new(), [0; 4]).unwrap();
+        assert_eq!(requests, [(String::from("ETHEREUM"), 1)]);
+
+        let session = ISession::new(1, 3, Vec::<u8>::new(), Vec::<u8>::new(), Vec::<u8>::new());
+        let context = IContext::new(1, String::from("ethereum"), Vec::<u8>::new(), Vec::<u8>::new(), Vec::<ISQoS>::new(), [0; 32], [0; 4], session);
+        contract.transport.set_context(Some(context));
+        let context = cross_chain_helper::cross_chain_receive(&mut contract).unwrap();
+        assert_eq!((context.from_chain, context.session.id), requests[0]);
+        assert_eq!(query_dests(&contract, ink::prelude::vec![String::from("NEAR")], |_, _| None).err(), Some(Error::DestNotRegistered));
+    }
+}
@@ -0,0 +1,115 @@
+use ink::prelude::string::String;
+use ink::storage::Mapping;
+use crate::cross_chain_helper::{
+    CrossChainBase,
+    Error,
+};
+use crate::remote_address::ChainFamily;
+
+/// Information of a registered chain.
+#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct ChainInfo {
+    /// Numeric id of the chain, unique in the registry.
+    pub id: u32,
+    /// Family of the chain, which decides its address format.
+    pub family: ChainFamily,
+}
+
+/// Returns the canonical form of `chain_name`, the surrounding whitespaces are trimmed, and letters are uppercase.
+pub fn normalize(chain_name: &str) -> String {
+    chain_name.trim().to_ascii_uppercase()
+}
+
+/// Registry of chains keyed by canonical names, so "Ethereum" and "ETHEREUM" are the same chain.
+/// Expose it with `CrossChainBase::get_chain_registry`, and the helpers will only send messages to registered chains,
+/// with their canonical names.
+#[ink::storage_item]
+#[derive(Default)]
+pub struct ChainRegistry {
+    chains: Mapping<String, ChainInfo>,
+    names: Mapping<u32, String>,
+}
+
+impl ChainRegistry {
+    /// Returns the information of `chain_name`, which is normalized first.
+    pub fn get(& self, chain_name: &str) -> Option<ChainInfo> {
+        self.chains.get(normalize(chain_name))
+    }
+
+    /// Returns the canonical name of the chain `id`.
+    pub fn name_of(& self, id: u32) -> Option<String> {
+        self.names.get(id)
+    }
+
+    /// Returns the canonical name of `chain_name`, or `Error::UnknownChain` if it is not registered.
+    pub fn canonical_name(& self, chain_name: &str) -> Result<String, Error> {
+        let chain_name = normalize(chain_name);
+        if !self.chains.contains(&chain_name) {
+            return Err(Error::UnknownChain);
+        }
+
+        Ok(chain_name)
+    }
+
+    /// Registers `chain_name` with `info`, replacing its previous information.
+    /// Returns `Error::ChainIdInUse` if the id is used by another chain.
+    pub fn register(&mut self, chain_name: &str, info: ChainInfo) -> Result<(), Error> {
+        let chain_name = normalize(chain_name);
+        if chain_name.is_empty() {
+            return Err(Error::UnknownChain);
+        }
+        if let Some(name) = self.names.get(info.id) {
+            if name != chain_name {
+                return Err(Error::ChainIdInUse);
+            }
+        }
+
+        if let Some(previous) = self.chains.get(&chain_name) {
+            self.names.remove(previous.id);
+        }
+        self.chains.insert(&chain_name, &info);
+        self.names.insert(info.id, &chain_name);
+
+        Ok(())
+    }
+
+    /// Unregisters `chain_name`.
+    pub fn unregister(&mut self, chain_name: &str) {
+        let chain_name = normalize(chain_name);
+        if let Some(info) = self.chains.get(&chain_name) {
+            self.names.remove(info.id);
+            self.chains.remove(&chain_name);
+        }
+    }
+}
+
+/// Returns the canonical name of `chain_name` if the contract has a chain registry, and it is registered.
+/// If the contract has no chain registry, `chain_name` is only normalized.
+pub fn canonical_chain_name<T: CrossChainBase>(contract: &T, chain_name: &str) -> Result<String, Error> {
+    match contract.get_chain_registry() {
+        Some(registry) => registry.canonical_name(chain_name),
+        None => Ok(normalize(chain_name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// We test if chain names are normalized, and ids are unique.
+    #[ink::test]
+    fn chain_registry_works() {
+        let mut registry = ChainRegistry::default();
+        let ethereum = ChainInfo { id: 1, family: ChainFamily::Evm };
+        assert_eq!(registry.register(" Ethereum", ethereum), Ok(()));
+        assert_eq!(registry.get("ETHEREUM"), Some(ethereum));
+        assert_eq!(registry.canonical_name("ethereum"), Ok(String::from("ETHEREUM")));
+        assert_eq!(registry.canonical_name("NEAR"), Err(Error::UnknownChain));
+        assert_eq!(registry.register("NEAR", ChainInfo { id: 1, family: ChainFamily::Near }), Err(Error::ChainIdInUse));
+
+        registry.unregister("ethereum");
+        assert_eq!(registry.get("ETHEREUM"), None);
+        assert_eq!(registry.name_of(1), None);
+    }
+}
@@ -0,0 +1,294 @@
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use ink::storage::Mapping;
+use scale::{
+    Decode,
+    Encode,
+};
+use payload::message_define::{
+    ISQoS,
+    IContent,
+    IContext,
+    IRequestMessage,
+};
+use payload::message_protocol::{
+    MsgDetail,
+    MessagePayload,
+};
+use crate::cross_chain_helper::{
+    self,
+    CrossChainBase,
+    Error,
+};
+
+/// Name of the payload item which carries a chunk.
+pub const CHUNK_ITEM: &str = "__chunk";
+
+/// Max number of chunks of a transfer, which bounds the storage used by an unfinished transfer.
+pub const MAX_CHUNKS: u32 = 1024;
+
+/// A numbered part of a payload which is too large for one message.
+#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct Chunk {
+    /// Id of the transfer, unique per source chain and sender.
+    pub transfer: u128,
+    /// Index of the chunk, starting from 0.
+    pub index: u32,
+    /// Number of chunks of the transfer.
+    pub total: u32,
+    /// Blake2 256 hash of the whole encoded payload.
+    pub hash: [u8; 32],
+    /// Part of the encoded payload.
+    pub data: Vec<u8>,
+}
+
+/// Returns the Blake2 256 hash of `data`.
+fn blake2_256(data: &[u8]) -> [u8; 32] {
+    let mut output = [0u8; 32];
+    ink::env::hash_bytes::<ink::env::hash::Blake2x256>(data, &mut output);
+    output
+}
+
+/// Splits `data` into chunks of at most `chunk_size` bytes for the transfer `transfer`.
+pub fn split(transfer: u128, data: &[u8], chunk_size: u32) -> Result<Vec<Chunk>, Error> {
+    if chunk_size == 0 {
+        return Err(Error::InvalidChunk);
+    }
+    let total = (data.len().max(1) + chunk_size as usize - 1) / chunk_size as usize;
+    if total > MAX_CHUNKS as usize {
+        return Err(Error::InvalidChunk);
+    }
+
+    let hash = blake2_256(data);
+    let mut chunks = Vec::new();
+    for index in 0..total {
+        let start = index * chunk_size as usize;
+        let end = data.len().min(start + chunk_size as usize);
+        chunks.push(Chunk {
+            transfer,
+            index: index as u32,
+            total: total as u32,
+            hash,
+            data: data[start..end].to_vec(),
+        });
+    }
+
+    Ok(chunks)
+}
+
+/// Number of blocks after which an unfinished transfer is expired, if the reassembler is created with `Default`, about one day.
+pub const DEFAULT_TRANSFER_TIMEOUT: u32 = 14400;
+
+/// Storage of chunked transfers.
+///
+/// It numbers the transfers sent, and keeps the chunks received per source chain, sender and transfer,
+/// until all of them arrive and the payload is reassembled.
+/// A transfer which is not finished `timeout` blocks after its first chunk arrives is expired,
+/// and it is started again by the next chunk of the same id, unless `timeout` is 0. It can also be aborted with `abort`.
+#[ink::storage_item]
+pub struct Reassembler {
+    next_transfer: u128,
+    timeout: u32,
+    transfers: Mapping<(String, Vec<u8>, u128), (u32, [u8; 32], u32, u32)>,
+    chunks: Mapping<(String, Vec<u8>, u128, u32), Vec<u8>>,
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRANSFER_TIMEOUT)
+    }
+}
+
+impl Reassembler {
+    /// Creates the reassembler, unfinished transfers are expired `timeout` blocks after their first chunk arrives.
+    /// If `timeout` is 0, they never expire, and can only be aborted.
+    pub fn new(timeout: u32) -> Self {
+        Self {
+            next_transfer: 0,
+            timeout,
+            transfers: Default::default(),
+            chunks: Default::default(),
+        }
+    }
+
+    /// Returns the number of chunks received of the transfer `transfer` from `sender` on `chain_name`, if it is unfinished.
+    pub fn received(& self, chain_name: &str, sender: &[u8], transfer: u128) -> Option<u32> {
+        self.transfers.get((chain_name, sender, transfer)).map(|(_, _, received, _)| received)
+    }
+
+    /// Removes the unfinished transfer `transfer` from `sender` on `chain_name`, and the chunks received of it.
+    pub fn abort(&mut self, chain_name: &str, sender: &[u8], transfer: u128) {
+        if let Some((total, _, _, _)) = self.transfers.get((chain_name, sender, transfer)) {
+            for index in 0..total {
+                self.chunks.remove((chain_name, sender, transfer, index));
+            }
+            self.transfers.remove((chain_name, sender, transfer));
+        }
+    }
+
+    /// Stores `chunk` received from `sender` on `chain_name`, and returns the payload once all the chunks of the transfer arrive.
+    /// The payload is only returned if its hash matches the one carried by the chunks.
+    pub fn receive(&mut self, chain_name: &str, sender: &[u8], chunk: Chunk) -> Result<Option<MessagePayload>, Error> {
+        if chunk.total == 0 || chunk.total > MAX_CHUNKS || chunk.index >= chunk.total {
+            return Err(Error::InvalidChunk);
+        }
+        let now = ink::env::block_number::<ink::env::DefaultEnvironment>();
+        let key = (chain_name, sender, chunk.transfer);
+        let (received, started_at) = match self.transfers.get(key) {
+            Some((_, _, _, started_at)) if self.timeout > 0 && now >= started_at.saturating_add(self.timeout) => {
+                self.abort(chain_name, sender, chunk.transfer);
+                (0, now)
+            },
+            Some((total, hash, received, started_at)) => {
+                if total != chunk.total || hash != chunk.hash {
+                    return Err(Error::InvalidChunk);
+                }
+                (received, started_at)
+            },
+            None => (0, now),
+        };
+        if self.chunks.contains((chain_name, sender, chunk.transfer, chunk.index)) {
+            return Err(Error::DuplicateMessage);
+        }
+
+        if received + 1 < chunk.total {
+            self.chunks.insert((chain_name, sender, chunk.transfer, chunk.index), &chunk.data);
+            self.transfers.insert(key, &(chunk.total, chunk.hash, received + 1, started_at));
+            return Ok(None);
+        }
+
+        let mut data = Vec::new();
+        for index in 0..chunk.total {
+            if index == chunk.index {
+                data.extend_from_slice(&chunk.data);
+            }
+            else {
+                data.extend_from_slice(&self.chunks.get((chain_name, sender, chunk.transfer, index)).unwrap_or_default());
+                self.chunks.remove((chain_name, sender, chunk.transfer, index));
+            }
+        }
+        self.transfers.remove(key);
+
+        if blake2_256(&data) != chunk.hash {
+            return Err(Error::ChunkHashMismatch);
+        }
+        MessagePayload::decode(&mut data.as_slice()).map(Some).map_err(|_| Error::InvalidEncoding)
+    }
+}
+
+/// If you want to send or receive payloads in chunks, this trait must be implemented.
+pub trait ChunkedTransfer: CrossChainBase {
+    /// Returns the chunked transfers of the contract.
+    fn get_reassembler_mut(&mut self) -> &mut Reassembler;
+}
+
+/// Sends `payload` in chunks of at most `chunk_size` bytes to the destination contract and action `dest` on `to_chain`.
+/// Every chunk is sent as a message, and the id of the transfer is returned.
+/// If sending a chunk fails, the chunks sent before are never reassembled, and the transfer should be sent again.
+pub fn cross_chain_send_chunked<T: ChunkedTransfer>(contract: &mut T, to_chain: String, sqos: Vec<ISQoS>, dest: (Vec<u8>, Vec<u8>), payload: MessagePayload, chunk_size: u32) -> Result<u128, Error> {
+    let reassembler = contract.get_reassembler_mut();
+    let transfer = reassembler.next_transfer;
+    reassembler.next_transfer = transfer + 1;
+
+    for chunk in split(transfer, &payload.encode(), chunk_size)? {
+        let mut msg_payload = MessagePayload::new();
+        msg_payload.push_item(String::from(CHUNK_ITEM), MsgDetail::InkU8Array(chunk.encode()));
+        let content = IContent::new(dest.0.clone(), dest.1.clone(), msg_payload.to_bytes());
+        let request = IRequestMessage::new(to_chain.clone(), sqos.clone(), content);
+        cross_chain_helper::cross_chain_send_message(contract, request)?;
+    }
+
+    Ok(transfer)
+}
+
+/// Receives a chunk, and handles the reassembled payload with `handler` once all the chunks of its transfer arrive.
+/// Returns if the payload is handled. It should be called by the message registered as the destination of chunked transfers.
+pub fn cross_chain_receive_chunk<T, E, F>(contract: &mut T, payload: MessagePayload, handler: F) -> Result<bool, Error>
+where
+    T: ChunkedTransfer,
+    F: FnOnce(&mut T, &IContext, &MessagePayload) -> Result<(), E>,
+{
+    let context = cross_chain_helper::cross_chain_receive(contract)?;
+
+    let chunk = payload.get_item(String::from(CHUNK_ITEM))
+        .and_then(|item| item.in_to::<Vec<u8>>())
+        .and_then(|bytes| Chunk::decode(&mut bytes.as_slice()).ok())
+        .ok_or(Error::InvalidChunk)?;
+    match contract.get_reassembler_mut().receive(&context.from_chain, &context.sender, chunk)? {
+        Some(payload) => {
+            handler(contract, &context, &payload).map_err(|_| Error::HandlerFailed)?;
+            Ok(true)
+        },
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// We test if chunks are reassembled in any order, and tampered ones are rejected.
+    #[ink::test]
+    fn reassembler_works() {
+        let mut payload = MessagePayload::new();
+        payload.push_item(String::from("greeting"), MsgDetail::InkStringArray(ink::prelude::vec![String::from("Hello, Polkadot")]));
+        let data = payload.encode();
+        let chunks = split(0, &data, 8).unwrap();
+        assert!(chunks.len() > 2);
+
+        let mut reassembler = Reassembler::new(10);
+        let sender = [0x01; 20];
+        let last = chunks.len() - 1;
+        assert!(reassembler.receive("ETHEREUM", &sender, chunks[last].clone()).unwrap().is_none());
+        assert_eq!(reassembler.receive("ETHEREUM", &sender, chunks[last].clone()).err(), Some(Error::DuplicateMessage));
+        assert!(reassembler.receive("ETHEREUM", &[0x02; 20], chunks[last].clone()).unwrap().is_none());
+        for chunk in chunks[1..last].iter() {
+            assert!(reassembler.receive("ETHEREUM", &sender, chunk.clone()).unwrap().is_none());
+        }
+        assert_eq!(reassembler.receive("ETHEREUM", &sender, chunks[0].clone()).unwrap().map(|payload| payload.encode()), Some(data));
+        assert_eq!(reassembler.received("ETHEREUM", &sender, 0), None);
+        assert_eq!(reassembler.received("ETHEREUM", &[0x02; 20], 0), Some(1));
+
+        let mut tampered = split(1, &[1, 2, 3], 8).unwrap().remove(0);
+        tampered.data[0] = 0;
+        assert_eq!(reassembler.receive("ETHEREUM", &sender, tampered).err(), Some(Error::ChunkHashMismatch));
+    }
+
+    /// We test if a transfer claimed with other chunks is started again once it expires, or after it is aborted.
+    #[ink::test]
+    fn expiry_and_abort_work() {
+        let sender = [0x01; 20];
+        let bogus = split(0, &[0; 20], 8).unwrap().remove(0);
+        let chunks = split(0, &[1; 20], 8).unwrap();
+        let mut reassembler = Reassembler::new(2);
+        assert!(reassembler.receive("ETHEREUM", &sender, bogus.clone()).unwrap().is_none());
+        assert_eq!(reassembler.receive("ETHEREUM", &sender, chunks[0].clone()).err(), Some(Error::InvalidChunk));
+
+        ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+        ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+        assert!(reassembler.receive("ETHEREUM", &sender, chunks[0].clone()).unwrap().is_none());
+        assert_eq!(reassembler.received("ETHEREUM", &sender, 0), Some(1));
+
+        reassembler.abort("ETHEREUM", &sender, 0);
+        assert_eq!(reassembler.received("ETHEREUM", &sender, 0), None);
+        assert!(reassembler.receive("ETHEREUM", &sender, bogus).unwrap().is_none());
+    }
+
+    /// We test if transfers never expire with a timeout of 0, so ones of many chunks finish.
+    #[ink::test]
+    fn zero_timeout_never_expires() {
+        let sender = [0x01; 20];
+        let mut payload = MessagePayload::new();
+        payload.push_item(String::from("greeting"), MsgDetail::InkStringArray(ink::prelude::vec![String::from("Hello, Polkadot")]));
+        let chunks = split(0, &payload.encode(), 8).unwrap();
+        let mut reassembler = Reassembler::new(0);
+        let last = chunks.len() - 1;
+        for chunk in chunks[..last].iter() {
+            assert!(reassembler.receive("ETHEREUM", &sender, chunk.clone()).unwrap().is_none());
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+        }
+        assert_eq!(reassembler.received("ETHEREUM", &sender, 0), Some(last as u32));
+        assert!(reassembler.receive("ETHEREUM", &sender, chunks[last].clone()).unwrap().is_some());
+    }
+}
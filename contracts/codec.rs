@@ -0,0 +1,135 @@
+use ink::prelude::boxed::Box;
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use scale::{
+    Decode,
+    Encode,
+};
+use payload::message_define::{
+    ISQoS,
+    IContent,
+    IContext,
+    IRequestMessage,
+};
+use crate::MultiDestContracts;
+use crate::cross_chain_helper::{
+    self,
+    CrossChainBase,
+    Error,
+};
+
+/// Encodings of the data sent to destination contracts.
+/// It is registered per destination with `DestEncodings::set_dest_encoding`, which needs the feature `near-codec`.
+#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum PayloadEncoding {
+    /// SCALE encoded `MessagePayload`, which the peer contracts of the SDK understand.
+    Scale,
+    /// Borsh, which Near contracts use for binary arguments, needs the feature `near-codec`.
+    Borsh,
+    /// JSON, which Near contracts use by default, needs the feature `near-codec`.
+    Json,
+}
+
+impl Default for PayloadEncoding {
+    fn default() -> Self {
+        PayloadEncoding::Scale
+    }
+}
+
+/// Encodes values of `V` sent to other chains, and decodes the ones received.
+/// Implement it to plug in another format, e.g. a compact one of the application.
+pub trait PayloadCodec<V> {
+    /// Encodes `value` as the data of a message.
+    fn encode(& self, value: &V) -> Result<Vec<u8>, Error>;
+
+    /// Decodes the data of a message as `V`.
+    fn decode(& self, data: &[u8]) -> Result<V, Error>;
+}
+
+/// The SCALE codec, which is the format of `MessagePayload`, and of raw SCALE structs.
+pub struct ScaleCodec;
+
+impl<V: Encode + Decode> PayloadCodec<V> for ScaleCodec {
+    fn encode(& self, value: &V) -> Result<Vec<u8>, Error> {
+        Ok(value.encode())
+    }
+
+    fn decode(& self, data: &[u8]) -> Result<V, Error> {
+        V::decode(&mut &data[..]).map_err(|_| Error::InvalidEncoding)
+    }
+}
+
+/// If you want to choose the codec of the data exchanged with each destination, this trait must be implemented.
+pub trait PayloadCodecs<V>: CrossChainBase + MultiDestContracts {
+    /// Returns the codec of the data exchanged with the destination contract registered for `action` on `chain_name`.
+    /// Return `Box::new(ScaleCodec)` for `MessagePayload`, which the peer contracts of the SDK understand,
+    /// or, with the feature `near-codec`, `near_codec::dest_codec` to follow the encoding registered in `DestEncodings`.
+    fn get_payload_codec(& self, chain_name: &str, action: &str) -> Box<dyn PayloadCodec<V>>;
+}
+
+/// Sends `value` encoded with `codec` to the destination contract registered for `action` on `chain_name`, and returns the message id.
+/// If `callback` is set, it is sent as a call like `cross_chain_call`.
+/// The helpers which send values to registered destinations use it.
+pub fn cross_chain_send_with<V, T: CrossChainBase + MultiDestContracts>(contract: &mut T, chain_name: String, action: String, codec: &dyn PayloadCodec<V>, value: &V, callback: Option<[u8; 4]>) -> Result<u128, Error> {
+    let (dest_contract, dest_action) = contract.get_dest_contract_info(chain_name.clone(), action).ok_or(Error::DestNotRegistered)?;
+    let data = codec.encode(value)?;
+
+    let content = IContent::new(dest_contract, dest_action, data);
+    let request = IRequestMessage::new(chain_name, Vec::<ISQoS>::new(), content);
+    match callback {
+        Some(callback) => cross_chain_helper::cross_chain_call(contract, request, callback),
+        None => cross_chain_helper::cross_chain_send_message(contract, request),
+    }
+}
+
+/// Sends `value` to the destination contract registered for `action` on `chain_name`, and returns the message id.
+/// `value` is encoded with the codec of the destination.
+pub fn cross_chain_send_encoded<V, T: PayloadCodecs<V>>(contract: &mut T, chain_name: String, action: String, value: &V) -> Result<u128, Error> {
+    let codec = contract.get_payload_codec(&chain_name, &action);
+    cross_chain_send_with(contract, chain_name, action, codec.as_ref(), value, None)
+}
+
+/// Sends `value` to the destination contract registered for `action` on `chain_name` like `cross_chain_call`, and returns the message id.
+/// `value` is encoded with the codec of the destination.
+pub fn cross_chain_call_encoded<V, T: PayloadCodecs<V>>(contract: &mut T, chain_name: String, action: String, value: &V, callback: [u8; 4]) -> Result<u128, Error> {
+    let codec = contract.get_payload_codec(&chain_name, &action);
+    cross_chain_send_with(contract, chain_name, action, codec.as_ref(), value, Some(callback))
+}
+
+/// Sends `value` to the destination contracts registered for `action` on every chain in `chains` like `cross_chain_broadcast`.
+/// `value` is encoded with the codec of each destination. Returns the message id, or the failure, of each chain.
+pub fn cross_chain_broadcast_encoded<V, T: PayloadCodecs<V>>(contract: &mut T, action: String, value: &V, chains: Vec<String>) -> Vec<(String, Result<u128, Error>)> {
+    let mut results = Vec::new();
+    for chain_name in chains {
+        let codec = contract.get_payload_codec(&chain_name, &action);
+        let result = cross_chain_send_with(contract, chain_name.clone(), action.clone(), codec.as_ref(), value, None);
+        results.push((chain_name, result));
+    }
+
+    results
+}
+
+/// Receives a cross-chain message like `cross_chain_receive`, and decodes its raw `data` with the codec of the destination
+/// registered for `action` on its source chain. Returns the context and the decoded value.
+/// Messages which take data of other formats should take it as `Vec<u8>`, and call it instead of `cross_chain_receive`.
+pub fn cross_chain_receive_decoded<V, T: PayloadCodecs<V>>(contract: &mut T, action: &str, data: &[u8]) -> Result<(IContext, V), Error> {
+    let context = cross_chain_helper::cross_chain_receive(contract)?;
+    let value = contract.get_payload_codec(&context.from_chain, action).decode(data)?;
+
+    Ok((context, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// We test if the SCALE codec decodes what it encodes, and rejects invalid data.
+    #[test]
+    fn scale_codec_works() {
+        let value = (7u32, String::from("Hello"));
+        let data = ScaleCodec.encode(&value).unwrap();
+        assert_eq!(PayloadCodec::<(u32, String)>::decode(&ScaleCodec, &data), Ok(value));
+        assert_eq!(PayloadCodec::<(u32, String)>::decode(&ScaleCodec, &data[..3]), Err(Error::InvalidEncoding));
+    }
+}
@@ -0,0 +1,202 @@
+use ink::prelude::string::String;
+use ink::prelude::vec;
+use ink::prelude::vec::Vec;
+use scale::{
+    Decode,
+    Encode,
+};
+use payload::message_define::{
+    ISQoS,
+    IContent,
+    IContext,
+    IRequestMessage,
+};
+use payload::message_protocol::{
+    MsgDetail,
+    MessagePayload,
+};
+use crate::limits;
+use crate::cross_chain_helper::{
+    self,
+    CrossChainBase,
+    Error,
+};
+
+/// Name of the payload item which carries a compressed payload, its presence is the compression flag.
+pub const COMPRESSED_ITEM: &str = "__compressed";
+
+/// Max size of a decompressed payload, which protects receivers from decompression bombs.
+pub const MAX_DECOMPRESSED_LEN: usize = 64 * 1024;
+
+/// Shortest match which is encoded as a back reference.
+const MIN_MATCH: usize = 3;
+
+/// Longest match which can be encoded in one token.
+const MAX_MATCH: usize = MIN_MATCH + 0x7f;
+
+/// Longest literal run which can be encoded in one token.
+const MAX_LITERALS: usize = 0x80;
+
+/// Farthest back reference which can be encoded.
+const MAX_OFFSET: usize = 0xffff;
+
+/// Number of entries of the match finder, a power of two.
+const HASH_SIZE: usize = 4096;
+
+/// Returns the slot of the match finder of the 3 bytes at `at` of `data`.
+fn hash_at(data: &[u8], at: usize) -> usize {
+    let value = (data[at] as u32) | (data[at + 1] as u32) << 8 | (data[at + 2] as u32) << 16;
+    (value.wrapping_mul(2654435761) >> 20) as usize & (HASH_SIZE - 1)
+}
+
+/// Appends `literals` as literal runs.
+fn push_literals(out: &mut Vec<u8>, literals: &[u8]) {
+    for run in literals.chunks(MAX_LITERALS) {
+        out.push((run.len() - 1) as u8);
+        out.extend_from_slice(run);
+    }
+}
+
+/// Compresses `data` with a simple LZ77 scheme.
+///
+/// The output starts with the length of `data` as a little-endian `u32`, followed by tokens.
+/// A token byte below `0x80` is followed by that many plus one literal bytes;
+/// otherwise its low 7 bits plus 3 are the length of a match, followed by its offset as a little-endian `u16`.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+
+    let mut table = vec![usize::MAX; HASH_SIZE];
+    let mut literal_start = 0;
+    let mut pos = 0;
+    while pos + MIN_MATCH <= data.len() {
+        let slot = hash_at(data, pos);
+        let candidate = table[slot];
+        table[slot] = pos;
+
+        if candidate != usize::MAX && pos - candidate <= MAX_OFFSET && data[candidate..candidate + MIN_MATCH] == data[pos..pos + MIN_MATCH] {
+            let mut len = MIN_MATCH;
+            while len < MAX_MATCH && pos + len < data.len() && data[candidate + len] == data[pos + len] {
+                len = len + 1;
+            }
+
+            push_literals(&mut out, &data[literal_start..pos]);
+            out.push(0x80 | (len - MIN_MATCH) as u8);
+            out.extend_from_slice(&((pos - candidate) as u16).to_le_bytes());
+            pos = pos + len;
+            literal_start = pos;
+        }
+        else {
+            pos = pos + 1;
+        }
+    }
+    push_literals(&mut out, &data[literal_start..]);
+
+    out
+}
+
+/// Decompresses data compressed by `compress`.
+/// Returns `None` if `data` is malformed, or decompresses to more than `max_len` bytes.
+pub fn decompress(data: &[u8], max_len: usize) -> Option<Vec<u8>> {
+    let len = u32::from_le_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    if len > max_len {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(len);
+    let mut pos = 4;
+    while pos < data.len() {
+        let token = data[pos] as usize;
+        pos = pos + 1;
+        if token < 0x80 {
+            let run = data.get(pos..pos + token + 1)?;
+            out.extend_from_slice(run);
+            pos = pos + token + 1;
+        }
+        else {
+            let match_len = (token & 0x7f) + MIN_MATCH;
+            let offset = u16::from_le_bytes(data.get(pos..pos + 2)?.try_into().ok()?) as usize;
+            pos = pos + 2;
+            if offset == 0 || offset > out.len() {
+                return None;
+            }
+            // Matches can overlap the bytes they produce, so they are copied one byte at a time.
+            for _ in 0..match_len {
+                out.push(out[out.len() - offset]);
+            }
+        }
+        if out.len() > len {
+            return None;
+        }
+    }
+    if out.len() != len {
+        return None;
+    }
+
+    Some(out)
+}
+
+/// Returns a payload which carries `payload` compressed, flagged by `COMPRESSED_ITEM`.
+/// If compression does not make it smaller, `payload` is returned as it is.
+pub fn compress_payload(payload: MessagePayload) -> MessagePayload {
+    let data = payload.encode();
+    let compressed = compress(&data);
+
+    let mut msg_payload = MessagePayload::new();
+    msg_payload.push_item(String::from(COMPRESSED_ITEM), MsgDetail::InkU8Array(compressed));
+    if msg_payload.encode().len() < data.len() {
+        msg_payload
+    }
+    else {
+        payload
+    }
+}
+
+/// Returns the payload carried by `payload` if it is flagged as compressed, or `payload` as it is.
+pub fn decompress_payload(payload: MessagePayload) -> Result<MessagePayload, Error> {
+    let compressed = match payload.get_item(String::from(COMPRESSED_ITEM)) {
+        Some(item) => item.in_to::<Vec<u8>>().ok_or(Error::InvalidEncoding)?,
+        None => return Ok(payload),
+    };
+
+    let data = decompress(&compressed, MAX_DECOMPRESSED_LEN).ok_or(Error::InvalidEncoding)?;
+    MessagePayload::decode(&mut data.as_slice()).map_err(|_| Error::InvalidEncoding)
+}
+
+/// Sends `payload` compressed to the destination contract and action `dest` on `to_chain`, and returns the message id.
+/// The items of `payload` are checked against the payload limits of the chain before it is compressed.
+/// The destination should receive it with `cross_chain_receive_decompressed`.
+pub fn cross_chain_send_compressed<T: CrossChainBase>(contract: &mut T, to_chain: String, sqos: Vec<ISQoS>, dest: (Vec<u8>, Vec<u8>), payload: MessagePayload) -> Result<u128, Error> {
+    cross_chain_helper::check_payload_items(contract, &to_chain, &limits::items_of(&payload))?;
+    let content = IContent::new(dest.0, dest.1, compress_payload(payload).to_bytes());
+    let request = IRequestMessage::new(to_chain, sqos, content);
+
+    cross_chain_helper::cross_chain_send_message(contract, request)
+}
+
+/// Receives a cross-chain message like `cross_chain_receive`, and returns the context with the payload decompressed.
+/// Payloads which are not flagged as compressed are returned as they are.
+pub fn cross_chain_receive_decompressed<T: CrossChainBase>(contract: &mut T, payload: MessagePayload) -> Result<(IContext, MessagePayload), Error> {
+    let context = cross_chain_helper::cross_chain_receive(contract)?;
+
+    Ok((context, decompress_payload(payload)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// We test if data is restored after compression, repetitive data shrinks, and malformed data is rejected.
+    #[test]
+    fn compression_works() {
+        let greetings = "Hello, Polkadot! Hello, Near! Hello, Ethereum! Hello, Flow! ".repeat(8);
+        for data in [&b""[..], &b"ab"[..], &[7u8; 300][..], greetings.as_bytes()] {
+            assert_eq!(decompress(&compress(data), MAX_DECOMPRESSED_LEN).as_deref(), Some(data));
+        }
+        assert!(compress(greetings.as_bytes()).len() < greetings.len() / 4);
+
+        let compressed = compress(&[7u8; 300]);
+        assert_eq!(decompress(&compressed, 299), None);
+        assert_eq!(decompress(&compressed[..compressed.len() - 1], MAX_DECOMPRESSED_LEN), None);
+    }
+}
@@ -0,0 +1,140 @@
+use ink::prelude::string::String;
+use payload::message_define::IContext;
+use crate::chain_registry;
+use crate::cross_chain_helper::{
+    self,
+    CrossChainBase,
+    Error,
+};
+use crate::remote_address::{
+    ChainFamily,
+    RemoteAddress,
+};
+
+/// Session types of cross-chain messages.
+#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum SessionType {
+    /// A message which needs no response, session type `1`.
+    Send,
+    /// A call which needs a response, session type `2`.
+    Call,
+    /// The response of a call, session type `3`.
+    Callback,
+    /// A local error of a sent message, session type `104`.
+    LocalError,
+    /// A remote error of a sent message, session type `105`.
+    RemoteError,
+}
+
+impl SessionType {
+    /// Returns the session type of `session_type` carried in `ISession`, if it is known.
+    pub fn from_session_type(session_type: u8) -> Option<Self> {
+        match session_type {
+            1 => Some(SessionType::Send),
+            2 => Some(SessionType::Call),
+            3 => Some(SessionType::Callback),
+            104 => Some(SessionType::LocalError),
+            105 => Some(SessionType::RemoteError),
+            _ => None,
+        }
+    }
+}
+
+/// Context of the cross-chain message being delivered, with the session type and the sender decoded.
+#[derive(Debug, Clone)]
+pub struct CrossChainContext {
+    /// Id of the message.
+    pub id: u128,
+    /// Name of the source chain.
+    pub from_chain: String,
+    /// Session type of the message.
+    pub session_type: SessionType,
+    /// Sender of the message, `None` if the chain family of the source chain is unknown.
+    pub sender: Option<RemoteAddress>,
+    /// The raw context got from the cross-chain contract.
+    pub raw: IContext,
+}
+
+impl CrossChainContext {
+    /// Decodes `context`, the sender is decoded as an address of `family` if it is known.
+    pub fn new(context: IContext, family: Option<ChainFamily>) -> Result<Self, Error> {
+        let session_type = SessionType::from_session_type(context.session.session_type).ok_or(Error::UnknownSessionType)?;
+        let sender = match family {
+            Some(family) => Some(RemoteAddress::from_bytes(family, &context.sender).ok_or(Error::InvalidAddress)?),
+            None => None,
+        };
+
+        Ok(Self {
+            id: context.id,
+            from_chain: context.from_chain.clone(),
+            session_type,
+            sender,
+            raw: context,
+        })
+    }
+}
+
+/// Returns the decoded context of the message being delivered, with the normalized name of its source chain
+/// like `cross_chain_receive_context`.
+/// Returns `Error::NoContext` if it is called outside a cross-chain delivery.
+pub fn cross_chain_context<T: CrossChainBase>(contract: &T) -> Result<CrossChainContext, Error> {
+    let mut context = cross_chain_helper::get_context(contract).ok_or(Error::NoContext)?;
+    context.from_chain = chain_registry::normalize(&context.from_chain);
+    let family = contract.get_chain_family(&context.from_chain);
+
+    CrossChainContext::new(context, family)
+}
+
+/// Receives the message being delivered like `cross_chain_receive`, and returns its decoded context.
+pub fn cross_chain_receive_context<T: CrossChainBase>(contract: &mut T) -> Result<CrossChainContext, Error> {
+    let context = cross_chain_helper::cross_chain_receive(contract)?;
+    let family = contract.get_chain_family(&context.from_chain);
+
+    CrossChainContext::new(context, family)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ink::prelude::vec::Vec;
+    use payload::message_define::{
+        ISession,
+        ISQoS,
+    };
+    use crate::transport::CrossChainTransport;
+    use crate::mock::MockTransport;
+
+    #[derive(Default)]
+    struct MockContract {
+        transport: MockTransport,
+    }
+
+    impl CrossChainBase for MockContract {
+        fn get_transport(& self) -> &dyn CrossChainTransport {
+            &self.transport
+        }
+    }
+
+    /// We test if session types are decoded, and unknown ones are rejected.
+    #[test]
+    fn session_type_works() {
+        assert_eq!(SessionType::from_session_type(2), Some(SessionType::Call));
+        assert_eq!(SessionType::from_session_type(105), Some(SessionType::RemoteError));
+        assert_eq!(SessionType::from_session_type(0), None);
+    }
+
+    /// We test if the context read without receiving the message has the normalized name of the source chain.
+    #[ink::test]
+    fn cross_chain_context_works() {
+        let contract = MockContract::default();
+        assert_eq!(cross_chain_context(&contract).err(), Some(Error::NoContext));
+
+        let session = ISession::new(1, 1, Vec::<u8>::new(), Vec::<u8>::new(), Vec::<u8>::new());
+        let context = IContext::new(1, String::from(" ethereum"), Vec::<u8>::new(), Vec::<u8>::new(), Vec::<ISQoS>::new(), [0; 32], [0; 4], session);
+        contract.transport.set_context(Some(context));
+        let context = cross_chain_context(&contract).unwrap();
+        assert_eq!(context.from_chain, "ETHEREUM");
+        assert_eq!(context.session_type, SessionType::Send);
+    }
+}
@@ -1,4 +1,5 @@
 use ink::primitives::AccountId;
+use ink::prelude::string::String;
 use ink::prelude::vec::Vec;
 use payload::message_define::{
     ISentMessage,
@@ -9,14 +10,41 @@ use payload::message_define::{
     IRequestMessage,
     IResponseMessage,
 };
+use payload::message_protocol::{
+    MsgDetail,
+    MessagePayload,
+};
+use crate::{
+    PauseState,
+    Direction,
+    MultiDestContracts,
+};
+use crate::replay_guard::ReplayGuard;
+use crate::failover::CrossChainContracts;
+use crate::message_status::{
+    MessageStatus,
+    MessageStatusRecord,
+};
+use crate::retry::{
+    KeptMessage,
+    RetryStore,
+};
+use crate::transport::{
+    CrossChainTransport,
+    DanteTransport,
+};
+use crate::remote_address::ChainFamily;
+use crate::limits::PayloadLimits;
+use crate::chain_registry::{
+    self,
+    ChainRegistry,
+};
+use crate::codec::{
+    self,
+    ScaleCodec,
+};
     
 const CROSS_CHAIN_CONTRACT_ADDRESS: &str = "0x9b33e9dbcc468833b9cec8e0642e4932487931ea092d789ffe51ee41fea4de7a";
-const SEND_MESSAGE_SELECTOR: [u8; 4] = [0x27, 0x26, 0x79, 0x17];
-// const REGISTER_SQOS_SELECTOR: [u8; 4] = [0x32, 0x80, 0x5c, 0x58];
-const GET_CONTEXT_SELECTOR: [u8; 4] = [0xee, 0xe9, 0xc1, 0xb3];
-const GET_SQOS_SELECTOR: [u8; 4] = [0x8d, 0xe9, 0x09, 0xd7];
-const SET_SQOS_SELECTOR: [u8; 4] = [0xc1, 0xe9, 0xbc, 0xda];
-const REMOVE_SQOS_SELECTOR: [u8; 4] = [0x35, 0x2b, 0x71, 0xb5];
 
 /// Converts hex string of address into [u8; 32]
 fn convert_address(s: &str) -> AccountId {
@@ -35,6 +63,82 @@ fn convert_address(s: &str) -> AccountId {
     AccountId::try_from(v).unwrap()
 }
 
+/// Errors returned by the cross-chain helpers.
+#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Error {
+    /// Messages from or to the chain are paused.
+    Paused,
+    /// No message is being delivered by the cross-chain contract.
+    NoContext,
+    /// The message has been processed before.
+    DuplicateMessage,
+    /// The ordered message carries no sequence number.
+    MissingSequence,
+    /// The sequence number of the ordered message is too far ahead of the expected one.
+    SequenceOutOfWindow,
+    /// No destination contract is registered for the chain and action.
+    DestNotRegistered,
+    /// The call to the cross-chain contract failed.
+    CallFailed,
+    /// The threshold of the query is zero, or greater than the number of chains.
+    InvalidThreshold,
+    /// The query group does not exist, or is finished.
+    UnknownQuery,
+    /// The query has not expired yet.
+    QueryNotExpired,
+    /// The forwarded message carries no valid envelope.
+    InvalidEnvelope,
+    /// The delay of the timelock can not be decreased.
+    InvalidDelay,
+    /// The cross-chain contract has been set.
+    AlreadyInitialized,
+    /// No cross-chain contract is proposed.
+    NoPendingRotation,
+    /// The delay of the proposed cross-chain contract has not passed.
+    RotationNotReady,
+    /// No copy of the message is kept for retrying.
+    MessageNotKept,
+    /// The message has been sent the max attempts.
+    TooManyAttempts,
+    /// The backoff of the message has not passed.
+    RetryTooEarly,
+    /// The dead letter does not exist.
+    DeadLetterNotFound,
+    /// The handler of the message failed.
+    HandlerFailed,
+    /// The encoded result exceeds the size cap of the store.
+    ResultTooLarge,
+    /// The session type of the message is unknown.
+    UnknownSessionType,
+    /// The address does not match the chain family.
+    InvalidAddress,
+    /// The chain is not registered.
+    UnknownChain,
+    /// The chain id is used by another chain.
+    ChainIdInUse,
+    /// The data can not be encoded with the encoding.
+    UnsupportedEncoding,
+    /// The data is not valid in the encoding.
+    InvalidEncoding,
+    /// The payload exceeds the max size of the destination chain.
+    PayloadTooLarge,
+    /// The payload has more items than the max number of the destination chain.
+    TooManyItems,
+    /// An item name or a string value is longer than the max length of the destination chain.
+    StringTooLong,
+    /// The chunk is malformed, or does not match the other chunks of its transfer.
+    InvalidChunk,
+    /// The reassembled payload does not match the hash of its transfer.
+    ChunkHashMismatch,
+    /// The sender is not accepted by the relay, or is not a registered relay contract.
+    UntrustedSender,
+    /// No cross-chain contract in the list is available.
+    NoHealthyContract,
+    /// A response or an error of the message has arrived.
+    AlreadyAnswered,
+}
+
 /// If you want to use functionalities of the SDK, this trait must be implemented.
 pub trait CrossChainBase {
     /// Returns the cross-chain contract address.
@@ -42,146 +146,387 @@ pub trait CrossChainBase {
         let default_address = convert_address(CROSS_CHAIN_CONTRACT_ADDRESS);
         default_address
     }
+
+    /// Returns the pause flags of the contract, if it is pausable.
+    /// The helpers refuse to send or receive messages which are paused.
+    fn get_pause_state(& self) -> Option<&PauseState> {
+        None
+    }
+
+    /// Returns the records of processed messages, if the contract needs replay protection.
+    /// The helpers refuse to receive messages which have been processed.
+    fn get_replay_guard_mut(&mut self) -> Option<&mut ReplayGuard> {
+        None
+    }
+
+    /// Returns the ordered list of cross-chain contracts, if the contract needs failover.
+    /// If it is not empty, it is used instead of `get_cross_chain_contract_address` when sending messages.
+    fn get_cross_chain_contracts(& self) -> Option<&CrossChainContracts> {
+        None
+    }
+
+    /// Returns the ordered list of cross-chain contracts mutably, to record health status and handled messages.
+    fn get_cross_chain_contracts_mut(&mut self) -> Option<&mut CrossChainContracts> {
+        None
+    }
+
+    /// Returns the local status records of sent messages, if the contract needs them.
+    fn get_message_status_record(& self) -> Option<&MessageStatusRecord> {
+        None
+    }
+
+    /// Returns the local status records of sent messages mutably, to update them.
+    fn get_message_status_record_mut(&mut self) -> Option<&mut MessageStatusRecord> {
+        None
+    }
+
+    /// Returns the copies of outbound messages, if the contract needs to retry messages.
+    fn get_retry_store_mut(&mut self) -> Option<&mut RetryStore> {
+        None
+    }
+
+    /// Returns the registry of chains, if the contract needs canonical chain names.
+    /// The helpers refuse to send messages to chains which are not registered.
+    fn get_chain_registry(& self) -> Option<&ChainRegistry> {
+        None
+    }
+
+    /// Returns the chain family of `chain_name`, which is used to decode addresses from it.
+    /// By default, the family registered in the chain registry is returned.
+    fn get_chain_family(& self, chain_name: &str) -> Option<ChainFamily> {
+        self.get_chain_registry().and_then(|registry| registry.get(chain_name)).map(|info| info.family)
+    }
+
+    /// Returns the payload limits of `chain_name`, if payloads sent to it are limited.
+    /// The helpers refuse to send payloads which exceed them, before calling the cross-chain contract.
+    fn get_payload_limits(& self, _chain_name: &str) -> Option<PayloadLimits> {
+        None
+    }
+
+    /// Returns the transport through which the helpers talk to the cross-chain contract.
+    fn get_transport(& self) -> &dyn CrossChainTransport {
+        &DanteTransport
+    }
+
+    /// Called after a cross-chain message is sent to `chain_name`, e.g. to emit an event of the contract.
+    /// `session_type` tells a message (1), a call (2) and a response (3) apart.
+    fn on_message_sent(&mut self, _chain_name: &str, _id: u128, _action: &[u8], _session_type: u8) {
+    }
+
+    /// Called after a cross-chain message is accepted by `cross_chain_receive`, e.g. to emit an event of the contract.
+    fn on_message_received(&mut self, _context: &IContext) {
+    }
 }
 
-// /// Registers sqos
-// pub fn register_sqos<T: CrossChainBase>(contract: &T, sqos: ISQoS) {
-//     let cross_chain: AccountId = <T as CrossChainBase>::get_cross_chain_contract_address(&contract);
-    
-//     ink::env::call::build_call::<ink::env::DefaultEnvironment>()
-//             .call_type(
-//                 ink::env::call::Call::new()
-//                     .callee(cross_chain)
-//                     .gas_limit(0)
-//                     .transferred_value(0))
-//             .exec_input(
-//                 ink::env::call::ExecutionInput::new(ink::env::call::Selector::new(REGISTER_SQOS_SELECTOR))
-//                 .push_arg(sqos)
-//             )
-//             .returns::<()>()
-//             .fire()
-//             .unwrap();
-// }
-
-/// Sends a cross-chain message.
-fn send_message<T: CrossChainBase>(contract: &mut T, message: ISentMessage) -> u128 {
-    let cross_chain: AccountId = <T as CrossChainBase>::get_cross_chain_contract_address(&contract);
-    
-    let id: u128 = ink::env::call::build_call::<ink::env::DefaultEnvironment>()
-            .call_type(
-                ink::env::call::Call::new()
-                    .callee(cross_chain)
-                    .gas_limit(0)
-                    .transferred_value(0))
-            .exec_input(
-                ink::env::call::ExecutionInput::new(ink::env::call::Selector::new(SEND_MESSAGE_SELECTOR))
-                .push_arg(message)
-            )
-            .returns::<u128>()
-            .fire()
-            .unwrap();
-    id
+/// Returns `Error::Paused` if messages in `direction` from or to `chain_name` are paused.
+fn check_paused<T: CrossChainBase>(contract: &T, direction: Direction, chain_name: &str) -> Result<(), Error> {
+    match contract.get_pause_state() {
+        Some(state) if state.is_paused(direction, chain_name) => Err(Error::Paused),
+        _ => Ok(()),
+    }
+}
+
+/// Returns an error if `data` exceeds the payload limits of `chain_name`, see `PayloadLimits::check_data`.
+fn check_payload_data<T: CrossChainBase>(contract: &T, chain_name: &str, data: &[u8]) -> Result<(), Error> {
+    match contract.get_payload_limits(chain_name) {
+        Some(limits) => limits.check_data(data),
+        None => Ok(()),
+    }
+}
+
+/// Sends a cross-chain message to `chain_name`.
+/// If the contract has a list of cross-chain contracts, the available ones are tried in order,
+/// the one which handles the message is recorded, and the id of the message in the namespace of the list is returned.
+fn send_message<T: CrossChainBase>(contract: &mut T, chain_name: &str, message: ISentMessage) -> Result<u128, Error> {
+    let candidates = match contract.get_cross_chain_contracts() {
+        Some(contracts) if !contracts.is_empty() => contracts.available_contracts(),
+        _ => {
+            let cross_chain: AccountId = <T as CrossChainBase>::get_cross_chain_contract_address(&contract);
+            return contract.get_transport().send_message(cross_chain, message);
+        },
+    };
+    if candidates.is_empty() {
+        return Err(Error::NoHealthyContract);
+    }
+
+    for cross_chain in candidates {
+        let result = contract.get_transport().send_message(cross_chain, message.clone());
+        if let Some(contracts) = contract.get_cross_chain_contracts_mut() {
+            match result {
+                Ok(contract_id) => {
+                    contracts.set_healthy(&cross_chain, true);
+                    return Ok(contracts.record_handled_by(chain_name, &cross_chain, contract_id));
+                },
+                Err(_) => contracts.set_healthy(&cross_chain, false),
+            }
+        }
+        if result.is_ok() {
+            return result;
+        }
+    }
+
+    Err(Error::CallFailed)
+}
+
+/// Returns the cross-chain contracts which are queried and configured,
+/// the available ones in the list of cross-chain contracts if the contract has one.
+fn cross_chain_contracts<T: CrossChainBase>(contract: &T) -> Vec<AccountId> {
+    match contract.get_cross_chain_contracts() {
+        Some(contracts) if !contracts.is_empty() => contracts.available_contracts(),
+        _ => ink::prelude::vec![<T as CrossChainBase>::get_cross_chain_contract_address(&contract)],
+    }
+}
+
+/// Sends a cross-chain message to `chain`, records its status, keeps a copy of it for retrying unless it is a response,
+/// and calls `on_message_sent`.
+fn dispatch<T: CrossChainBase>(contract: &mut T, chain: String, action: Vec<u8>, session_type: u8, message: ISentMessage) -> Result<u128, Error> {
+    let copy = match contract.get_retry_store_mut() {
+        Some(_) if session_type != 3 => Some(message.clone()),
+        _ => None,
+    };
+
+    let id = send_message(contract, &chain, message)?;
+    if session_type != 3 {
+        if let Some(record) = contract.get_message_status_record_mut() {
+            record.set(&chain, id, MessageStatus::Sent);
+        }
+    }
+    if let (Some(message), Some(store)) = (copy, contract.get_retry_store_mut()) {
+        let kept = KeptMessage {
+            chain: chain.clone(),
+            action: action.clone(),
+            session_type,
+            message,
+            attempts: 1,
+            last_sent: ink::env::block_number::<ink::env::DefaultEnvironment>(),
+            retries: Vec::new(),
+        };
+        store.keep(id, &kept);
+    }
+    contract.on_message_sent(&chain, id, &action, session_type);
+
+    Ok(id)
 }
 
 /// Sends a cross-chain message, and returns the message id.
-pub fn cross_chain_send_message<T: CrossChainBase>(contract: &mut T, request: IRequestMessage) -> u128 {
+pub fn cross_chain_send_message<T: CrossChainBase>(contract: &mut T, request: IRequestMessage) -> Result<u128, Error> {
+    let chain = chain_registry::canonical_chain_name(contract, &request.to_chain)?;
+    check_paused(contract, Direction::Outbound, &chain)?;
+    check_payload_data(contract, &chain, &request.content.data)?;
+    let action = request.content.action.clone();
     let session = ISession::new(0, 1, Vec::<u8>::new(), Vec::<u8>::new(), Vec::<u8>::new());
-    let message = ISentMessage::new(request.to_chain, request.sqos, request.content, session);
+    let message = ISentMessage::new(chain.clone(), request.sqos, request.content, session);
 
-    send_message(contract, message)
+    dispatch(contract, chain, action, 1, message)
 }
 
 /// Sends a cross-chain message, and returns the message id.
 /// Latar a callback will be called.
-pub fn cross_chain_call<T: CrossChainBase>(contract: &mut T, request: IRequestMessage, callback: [u8; 4]) -> u128 {
+pub fn cross_chain_call<T: CrossChainBase>(contract: &mut T, request: IRequestMessage, callback: [u8; 4]) -> Result<u128, Error> {
+    let chain = chain_registry::canonical_chain_name(contract, &request.to_chain)?;
+    check_paused(contract, Direction::Outbound, &chain)?;
+    check_payload_data(contract, &chain, &request.content.data)?;
+    let action = request.content.action.clone();
     let session = ISession::new(0, 2, Vec::from(callback), Vec::<u8>::new(), Vec::<u8>::new());
-    let message = ISentMessage::new(request.to_chain, request.sqos, request.content, session);
+    let message = ISentMessage::new(chain.clone(), request.sqos, request.content, session);
 
-    send_message(contract, message)
+    dispatch(contract, chain, action, 2, message)
+}
+
+/// Returns an error if `items` exceed the max number of items or the max string length of `chain_name`,
+/// whose limits are looked up with its canonical name.
+pub fn check_payload_items<T: CrossChainBase>(contract: &T, chain_name: &str, items: &[(String, MsgDetail)]) -> Result<(), Error> {
+    let chain_name = chain_registry::canonical_chain_name(contract, chain_name)?;
+    match contract.get_payload_limits(&chain_name) {
+        Some(limits) => limits.check_items(items),
+        None => Ok(()),
+    }
+}
+
+/// Sends `items` as a `MessagePayload` to the destination contract registered for `action` on `chain_name`, and returns the message id.
+/// The items are checked against the payload limits of the chain, before the payload is built.
+pub fn cross_chain_send_items<T: CrossChainBase + MultiDestContracts>(contract: &mut T, chain_name: String, action: String, items: Vec<(String, MsgDetail)>) -> Result<u128, Error> {
+    check_payload_items(contract, &chain_name, &items)?;
+
+    let mut msg_payload = MessagePayload::new();
+    for (name, value) in items {
+        msg_payload.push_item(name, value);
+    }
+    codec::cross_chain_send_with(contract, chain_name, action, &ScaleCodec, &msg_payload, None)
+}
+
+/// Sends `payload` to the destination contracts registered for `action` on every chain in `chains`.
+/// Returns the message id, or the failure, of each chain.
+/// Use `codec::cross_chain_broadcast_encoded` to encode it with the codec of each destination.
+pub fn cross_chain_broadcast<T: CrossChainBase + MultiDestContracts>(contract: &mut T, action: String, payload: MessagePayload, chains: Vec<String>) -> Vec<(String, Result<u128, Error>)> {
+    let mut results = Vec::new();
+    for chain_name in chains {
+        let result = codec::cross_chain_send_with(contract, chain_name.clone(), action.clone(), &ScaleCodec, &payload, None);
+        results.push((chain_name, result));
+    }
+
+    results
 }
 
 /// Responds a cross-chain message, and returns the message id.
-pub fn cross_chain_respond<T: CrossChainBase>(contract: &mut T, response: IResponseMessage) -> u128 {
-    let context = get_context(contract).unwrap();
+pub fn cross_chain_respond<T: CrossChainBase>(contract: &mut T, response: IResponseMessage) -> Result<u128, Error> {
+    let mut context = get_context(contract).ok_or(Error::NoContext)?;
+    context.from_chain = chain_registry::normalize(&context.from_chain);
+    check_paused(contract, Direction::Outbound, &context.from_chain)?;
+    check_payload_data(contract, &context.from_chain, &response.data)?;
+    let chain = context.from_chain.clone();
+    let action = context.session.callback.clone();
     let session = ISession::new(context.id, 3, Vec::<u8>::new(), Vec::<u8>::new(), Vec::<u8>::new());
     let content = IContent::new(context.sender, context.session.callback, response.data);
     let message = ISentMessage::new(context.from_chain, response.sqos, content, session);
     
-    send_message(contract, message)
+    dispatch(contract, chain, action, 3, message)
+}
+
+/// Returns context of the message being received, with the normalized name of its source chain,
+/// if messages from the chain are not paused.
+/// If the contract has a `ReplayGuard`, the message is recorded as processed, and duplicates are rejected.
+/// If it is a response or an error, the id of the message it reports is mapped to the namespace of the list of cross-chain contracts,
+/// if the message was sent through it. Then the status of the message is updated, and the kept copy of it is removed.
+/// At last `on_message_received` is called with the context.
+/// It should be called at the beginning of every message which receives cross-chain messages.
+pub fn cross_chain_receive<T: CrossChainBase>(contract: &mut T) -> Result<IContext, Error> {
+    let cross_chain = delivering_contract(contract);
+    let mut context = contract.get_transport().get_context(cross_chain).ok_or(Error::NoContext)?;
+    context.from_chain = chain_registry::normalize(&context.from_chain);
+    check_paused(contract, Direction::Inbound, &context.from_chain)?;
+    if let Some(guard) = contract.get_replay_guard_mut() {
+        guard.mark_processed(&cross_chain, &context.from_chain, context.id)?;
+    }
+    if let Some(status) = MessageStatus::from_session_type(context.session.session_type) {
+        if let Some(id) = contract.get_cross_chain_contracts().and_then(|contracts| contracts.id_of(&cross_chain, &context.from_chain, context.session.id)) {
+            context.session.id = id;
+        }
+        if let Some(record) = contract.get_message_status_record_mut() {
+            record.set(&context.from_chain, context.session.id, status);
+        }
+        if let Some(store) = contract.get_retry_store_mut() {
+            store.remove(&context.from_chain, context.session.id);
+        }
+    }
+    contract.on_message_received(&context);
+
+    Ok(context)
+}
+
+/// Sends the kept copy of the message `id` sent to `chain_name` again, and returns the new message id, which is linked to the original one.
+/// The message can be retried only if no response or error of any attempt has arrived,
+/// it has been sent less than the max attempts, and the backoff has passed.
+pub fn retry_message<T: CrossChainBase>(contract: &mut T, chain_name: String, id: u128) -> Result<u128, Error> {
+    let chain = chain_registry::canonical_chain_name(contract, &chain_name)?;
+    let store = contract.get_retry_store_mut().ok_or(Error::MessageNotKept)?;
+    let original = store.original_of(&chain, id);
+    let mut kept = store.get(&chain, original).ok_or(Error::MessageNotKept)?;
+    if kept.attempts >= store.max_attempts() {
+        return Err(Error::TooManyAttempts);
+    }
+    let now = ink::env::block_number::<ink::env::DefaultEnvironment>();
+    if now < store.next_retry_at(&kept) {
+        return Err(Error::RetryTooEarly);
+    }
+    if let Some(record) = contract.get_message_status_record() {
+        let answered = core::iter::once(original).chain(kept.retries.iter().copied())
+            .any(|id| record.get(&chain, id).map_or(false, |status| status != MessageStatus::Sent));
+        if answered {
+            return Err(Error::AlreadyAnswered);
+        }
+    }
+    check_paused(contract, Direction::Outbound, &chain)?;
+
+    let new_id = send_message(contract, &chain, kept.message.clone())?;
+    if let Some(record) = contract.get_message_status_record_mut() {
+        record.set(&chain, new_id, MessageStatus::Sent);
+    }
+    kept.attempts = kept.attempts + 1;
+    kept.last_sent = now;
+    kept.retries.push(new_id);
+    if let Some(store) = contract.get_retry_store_mut() {
+        store.keep(original, &kept);
+    }
+    contract.on_message_sent(&chain, new_id, &kept.action, kept.session_type);
+
+    Ok(new_id)
+}
+
+/// Returns the status of the message `id` sent to `chain_name`, which is looked up with its canonical name.
+/// The local record is used if there is one, or the cross-chain contract which handled the message is queried,
+/// which returns `MessageStatus::Sent` if it has recorded the message.
+pub fn get_message_status<T: CrossChainBase>(contract: &T, chain_name: String, id: u128) -> Option<MessageStatus> {
+    let chain_name = chain_registry::canonical_chain_name(contract, &chain_name).ok()?;
+    if let Some(status) = contract.get_message_status_record().and_then(|record| record.get(&chain_name, id)) {
+        return Some(status);
+    }
+
+    let (cross_chain, id) = match contract.get_cross_chain_contracts().and_then(|contracts| contracts.handled_by(&chain_name, id)) {
+        Some(handled_by) => handled_by,
+        None => (*cross_chain_contracts(contract).first()?, id),
+    };
+    contract.get_transport().get_sent_message(cross_chain, chain_name, id).ok().flatten().map(|_| MessageStatus::Sent)
+}
+
+/// Returns the cross-chain contract which delivers the message being received,
+/// the caller if it is one of the cross-chain contracts of the contract.
+fn delivering_contract<T: CrossChainBase>(contract: &T) -> AccountId {
+    if let Some(contracts) = contract.get_cross_chain_contracts() {
+        let caller = ink::env::caller::<ink::env::DefaultEnvironment>();
+        if contracts.contains(&caller) {
+            return caller;
+        }
+    }
+
+    <T as CrossChainBase>::get_cross_chain_contract_address(&contract)
 }
 
 /// Returns context of Cross Chain
+/// If the caller is one of the cross-chain contracts of the contract, the context is got from it.
 pub fn get_context<T: CrossChainBase>(contract: &T) -> Option<IContext> {
-    let cross_chain: AccountId = <T as CrossChainBase>::get_cross_chain_contract_address(&contract);
-    
-    ink::env::call::build_call::<ink::env::DefaultEnvironment>()
-            .call_type(
-                ink::env::call::Call::new()
-                    .callee(cross_chain)
-                    .gas_limit(0)
-                    .transferred_value(0))
-            .exec_input(
-                ink::env::call::ExecutionInput::new(ink::env::call::Selector::new(GET_CONTEXT_SELECTOR))
-            )
-            .returns::<Option<IContext>>()
-            .fire()
-            .unwrap()
+    contract.get_transport().get_context(delivering_contract(contract))
 }
 
 /// Returns SQoS registered in Cross Chain
+/// If the contract has a list of cross-chain contracts, it is got from the first available one which answers.
 pub fn get_sqos<T: CrossChainBase>(contract: &T, account_id: AccountId) -> Option<ISQoS> {
-    let cross_chain: AccountId = <T as CrossChainBase>::get_cross_chain_contract_address(&contract);
-
-    ink::env::call::build_call::<ink::env::DefaultEnvironment>()
-            .call_type(
-                ink::env::call::Call::new()
-                    .callee(cross_chain)
-                    .gas_limit(0)
-                    .transferred_value(0))
-            .exec_input(
-                ink::env::call::ExecutionInput::new(ink::env::call::Selector::new(GET_SQOS_SELECTOR))
-                .push_arg(account_id)
-            )
-            .returns::<Option<ISQoS>>()
-            .fire()
-            .unwrap()
+    cross_chain_contracts(contract).into_iter()
+        .find_map(|cross_chain| contract.get_transport().get_sqos(cross_chain, account_id).ok())
+        .flatten()
+}
+
+/// Calls `call` with every cross-chain contract which is configured, see `cross_chain_contracts`.
+/// A contract whose call fails is marked as unhealthy, and the next one is called.
+/// Returns `Ok` if the call succeeds in at least one contract.
+fn configure<T: CrossChainBase>(contract: &mut T, call: impl Fn(&dyn CrossChainTransport, AccountId) -> Result<(), Error>) -> Result<(), Error> {
+    let candidates = cross_chain_contracts(contract);
+    if candidates.is_empty() {
+        return Err(Error::NoHealthyContract);
+    }
+
+    let mut result = Err(Error::CallFailed);
+    for cross_chain in candidates {
+        let healthy = call(contract.get_transport(), cross_chain).is_ok();
+        if let Some(contracts) = contract.get_cross_chain_contracts_mut() {
+            contracts.set_healthy(&cross_chain, healthy);
+        }
+        if healthy {
+            result = Ok(());
+        }
+    }
+
+    result
 }
 
 /// Sets SQoS registered in Cross Chain
-pub fn set_sqos<T: CrossChainBase>(contract: &T, sqos: ISQoS, account_id: AccountId) {
-    let cross_chain: AccountId = <T as CrossChainBase>::get_cross_chain_contract_address(&contract);
-    
-    ink::env::call::build_call::<ink::env::DefaultEnvironment>()
-            .call_type(
-                ink::env::call::Call::new()
-                    .callee(cross_chain)
-                    .gas_limit(0)
-                    .transferred_value(0))
-            .exec_input(
-                ink::env::call::ExecutionInput::new(ink::env::call::Selector::new(SET_SQOS_SELECTOR))
-                .push_arg(account_id)
-                .push_arg(sqos)
-            )
-            .returns::<()>()
-            .fire()
-            .unwrap();
+/// If the contract has a list of cross-chain contracts, it is set in every available one which answers.
+pub fn set_sqos<T: CrossChainBase>(contract: &mut T, sqos: ISQoS, account_id: AccountId) -> Result<(), Error> {
+    configure(contract, |transport, cross_chain| transport.set_sqos(cross_chain, sqos.clone(), account_id))
 }
 
 /// Remove SQoS registered in Cross Chain
-pub fn remove_sqos<T: CrossChainBase>(contract: &T, account_id: AccountId) {
-    let cross_chain: AccountId = <T as CrossChainBase>::get_cross_chain_contract_address(&contract);
-    
-    ink::env::call::build_call::<ink::env::DefaultEnvironment>()
-            .call_type(
-                ink::env::call::Call::new()
-                    .callee(cross_chain)
-                    .gas_limit(0)
-                    .transferred_value(0))
-            .exec_input(
-                ink::env::call::ExecutionInput::new(ink::env::call::Selector::new(REMOVE_SQOS_SELECTOR))
-                .push_arg(account_id)
-            )
-            .returns::<()>()
-            .fire()
-            .unwrap();
-}
\ No newline at end of file
+/// If the contract has a list of cross-chain contracts, it is removed from every available one which answers.
+pub fn remove_sqos<T: CrossChainBase>(contract: &mut T, account_id: AccountId) -> Result<(), Error> {
+    configure(contract, |transport, cross_chain| transport.remove_sqos(cross_chain, account_id))
+}
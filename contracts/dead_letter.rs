@@ -0,0 +1,100 @@
+use ink::prelude::vec::Vec;
+use ink::storage::Mapping;
+use payload::message_define::IContext;
+use payload::message_protocol::MessagePayload;
+use crate::cross_chain_helper::{
+    self,
+    CrossChainBase,
+    Error,
+};
+
+/// An inbound message whose handler failed.
+#[derive(scale::Encode, scale::Decode, Debug, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct DeadLetter {
+    /// The payload of the message.
+    pub payload: MessagePayload,
+    /// The context of the message.
+    pub context: IContext,
+    /// The SCALE encoded error returned by the handler.
+    pub error: Vec<u8>,
+}
+
+/// Storage of dead letters keyed by increasing ids.
+#[ink::storage_item]
+#[derive(Default)]
+pub struct DeadLetterQueue {
+    next_id: u128,
+    entries: Mapping<u128, DeadLetter>,
+}
+
+impl DeadLetterQueue {
+    /// Stores `dead_letter`, and returns its id.
+    pub fn push(&mut self, dead_letter: &DeadLetter) -> u128 {
+        let id = self.next_id;
+        self.next_id = id + 1;
+        self.entries.insert(id, dead_letter);
+        id
+    }
+
+    /// Returns the dead letter `id`.
+    pub fn get(& self, id: u128) -> Option<DeadLetter> {
+        self.entries.get(id)
+    }
+
+    /// Removes the dead letter `id`.
+    pub fn remove(&mut self, id: u128) {
+        self.entries.remove(id);
+    }
+}
+
+/// If you want to keep inbound messages whose handler fails, this trait must be implemented.
+pub trait DeadLetterHandling: CrossChainBase {
+    /// Returns the dead-letter queue of the contract.
+    fn get_dead_letter_queue_mut(&mut self) -> &mut DeadLetterQueue;
+}
+
+/// Receives a cross-chain message, and handles it with `handler`.
+/// If the handler fails, the payload and the context are stored in the dead-letter queue, and the id of the dead letter is returned.
+pub fn cross_chain_receive_or_dead_letter<T, E, F>(contract: &mut T, payload: MessagePayload, handler: F) -> Result<Option<u128>, Error>
+where
+    T: DeadLetterHandling,
+    E: scale::Encode,
+    F: FnOnce(&mut T, &IContext, &MessagePayload) -> Result<(), E>,
+{
+    let context = cross_chain_helper::cross_chain_receive(contract)?;
+
+    match handler(contract, &context, &payload) {
+        Ok(()) => Ok(None),
+        Err(error) => {
+            let dead_letter = DeadLetter {
+                payload,
+                context,
+                error: error.encode(),
+            };
+            Ok(Some(contract.get_dead_letter_queue_mut().push(&dead_letter)))
+        },
+    }
+}
+
+/// Handles the dead letter `id` with `handler` again, and removes it if the handler succeeds.
+pub fn replay_dead_letter<T, E, F>(contract: &mut T, id: u128, handler: F) -> Result<(), Error>
+where
+    T: DeadLetterHandling,
+    F: FnOnce(&mut T, &IContext, &MessagePayload) -> Result<(), E>,
+{
+    let dead_letter = contract.get_dead_letter_queue_mut().get(id).ok_or(Error::DeadLetterNotFound)?;
+    handler(contract, &dead_letter.context, &dead_letter.payload).map_err(|_| Error::HandlerFailed)?;
+    contract.get_dead_letter_queue_mut().remove(id);
+
+    Ok(())
+}
+
+/// Discards the dead letter `id`.
+pub fn discard_dead_letter<T: DeadLetterHandling>(contract: &mut T, id: u128) -> Result<(), Error> {
+    let queue = contract.get_dead_letter_queue_mut();
+    queue.get(id).ok_or(Error::DeadLetterNotFound)?;
+    queue.remove(id);
+
+    Ok(())
+}
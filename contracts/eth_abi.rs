@@ -0,0 +1,305 @@
+use ink::prelude::boxed::Box;
+use ink::prelude::string::String;
+use ink::prelude::vec;
+use ink::prelude::vec::Vec;
+use payload::message_protocol::MsgDetail;
+use crate::codec::PayloadCodec;
+use crate::cross_chain_helper::Error;
+
+/// Size of an ABI word in bytes.
+const WORD: usize = 32;
+
+/// Ethereum ABI types supported by the encoder.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AbiType {
+    /// `uint<M>`, values above `u128::MAX` can not be decoded.
+    Uint,
+    /// `int<M>`, values out of the range of `i128` can not be decoded.
+    Int,
+    /// `address`.
+    Address,
+    /// `bytes`.
+    Bytes,
+    /// `string`.
+    String,
+    /// `T[]`.
+    Array(Box<AbiType>),
+}
+
+impl AbiType {
+    /// Returns if the type is encoded in the tail part.
+    fn is_dynamic(& self) -> bool {
+        !matches!(self, AbiType::Uint | AbiType::Int | AbiType::Address)
+    }
+}
+
+/// A value encoded with the Ethereum ABI.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AbiValue {
+    /// `uint<M>`.
+    Uint(u128),
+    /// `int<M>`.
+    Int(i128),
+    /// `address`.
+    Address([u8; 20]),
+    /// `bytes`.
+    Bytes(Vec<u8>),
+    /// `string`.
+    String(String),
+    /// `T[]`, all the items have the same type.
+    Array(Vec<AbiValue>),
+}
+
+impl AbiValue {
+    /// Returns if the value is encoded in the tail part.
+    fn is_dynamic(& self) -> bool {
+        !matches!(self, AbiValue::Uint(_) | AbiValue::Int(_) | AbiValue::Address(_))
+    }
+
+    /// Converts a payload item to an ABI value, integers become `uint` or `int`, `u8` arrays become `bytes`.
+    /// Returns `None` for types which have no ABI counterpart.
+    pub fn from_msg_detail(detail: &MsgDetail) -> Option<Self> {
+        let uints = |values: Vec<u128>| AbiValue::Array(values.into_iter().map(AbiValue::Uint).collect());
+        let ints = |values: Vec<i128>| AbiValue::Array(values.into_iter().map(AbiValue::Int).collect());
+        let value = match detail {
+            MsgDetail::InkString(value) => AbiValue::String(value.clone()),
+            MsgDetail::InkU8(value) => AbiValue::Uint(*value as u128),
+            MsgDetail::InkU16(value) => AbiValue::Uint(*value as u128),
+            MsgDetail::InkU32(value) => AbiValue::Uint(*value as u128),
+            MsgDetail::InkU64(value) => AbiValue::Uint(*value as u128),
+            MsgDetail::InkU128(value) => AbiValue::Uint(*value),
+            MsgDetail::InkI8(value) => AbiValue::Int(*value as i128),
+            MsgDetail::InkI16(value) => AbiValue::Int(*value as i128),
+            MsgDetail::InkI32(value) => AbiValue::Int(*value as i128),
+            MsgDetail::InkI64(value) => AbiValue::Int(*value as i128),
+            MsgDetail::InkI128(value) => AbiValue::Int(*value),
+            MsgDetail::InkStringArray(values) => AbiValue::Array(values.iter().cloned().map(AbiValue::String).collect()),
+            MsgDetail::InkU8Array(values) => AbiValue::Bytes(values.clone()),
+            MsgDetail::InkU16Array(values) => uints(values.iter().map(|v| *v as u128).collect()),
+            MsgDetail::InkU32Array(values) => uints(values.iter().map(|v| *v as u128).collect()),
+            MsgDetail::InkU64Array(values) => uints(values.iter().map(|v| *v as u128).collect()),
+            MsgDetail::InkU128Array(values) => uints(values.clone()),
+            MsgDetail::InkI8Array(values) => ints(values.iter().map(|v| *v as i128).collect()),
+            MsgDetail::InkI16Array(values) => ints(values.iter().map(|v| *v as i128).collect()),
+            MsgDetail::InkI32Array(values) => ints(values.iter().map(|v| *v as i128).collect()),
+            MsgDetail::InkI64Array(values) => ints(values.iter().map(|v| *v as i128).collect()),
+            MsgDetail::InkI128Array(values) => ints(values.clone()),
+            #[allow(unreachable_patterns)]
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Converts an ABI value decoded from a response to a payload item.
+    /// `uint` and `int` become `u128` and `i128`, `address` and `bytes` become `u8` arrays.
+    /// Returns `None` for arrays of which items have no payload counterpart.
+    pub fn to_msg_detail(& self) -> Option<MsgDetail> {
+        let detail = match self {
+            AbiValue::Uint(value) => MsgDetail::InkU128(*value),
+            AbiValue::Int(value) => MsgDetail::InkI128(*value),
+            AbiValue::Address(value) => MsgDetail::InkU8Array(value.to_vec()),
+            AbiValue::Bytes(value) => MsgDetail::InkU8Array(value.clone()),
+            AbiValue::String(value) => MsgDetail::InkString(value.clone()),
+            AbiValue::Array(values) => match values.first() {
+                None | Some(AbiValue::Uint(_)) => MsgDetail::InkU128Array(values.iter().map(|v| match v {
+                    AbiValue::Uint(value) => Some(*value),
+                    _ => None,
+                }).collect::<Option<Vec<u128>>>()?),
+                Some(AbiValue::Int(_)) => MsgDetail::InkI128Array(values.iter().map(|v| match v {
+                    AbiValue::Int(value) => Some(*value),
+                    _ => None,
+                }).collect::<Option<Vec<i128>>>()?),
+                Some(AbiValue::String(_)) => MsgDetail::InkStringArray(values.iter().map(|v| match v {
+                    AbiValue::String(value) => Some(value.clone()),
+                    _ => None,
+                }).collect::<Option<Vec<String>>>()?),
+                _ => return None,
+            },
+        };
+
+        Some(detail)
+    }
+}
+
+/// Returns `value` as a big-endian ABI word.
+fn word(value: u128) -> [u8; WORD] {
+    let mut bytes = [0u8; WORD];
+    bytes[WORD - 16..].copy_from_slice(&value.to_be_bytes());
+    bytes
+}
+
+/// Appends `data` padded with zeros to a multiple of the word size, after its length.
+fn encode_bytes(out: &mut Vec<u8>, data: &[u8]) {
+    out.extend_from_slice(&word(data.len() as u128));
+    out.extend_from_slice(data);
+    let padding = (WORD - data.len() % WORD) % WORD;
+    out.extend(core::iter::repeat(0u8).take(padding));
+}
+
+/// Encodes `value` in place, for static values, or as the tail, for dynamic values.
+fn encode_value(out: &mut Vec<u8>, value: &AbiValue) {
+    match value {
+        AbiValue::Uint(value) => out.extend_from_slice(&word(*value)),
+        AbiValue::Int(value) => {
+            let fill = if *value < 0 { 0xff } else { 0x00 };
+            out.extend_from_slice(&[fill; WORD - 16]);
+            out.extend_from_slice(&value.to_be_bytes());
+        },
+        AbiValue::Address(address) => {
+            out.extend_from_slice(&[0u8; WORD - 20]);
+            out.extend_from_slice(address);
+        },
+        AbiValue::Bytes(data) => encode_bytes(out, data),
+        AbiValue::String(text) => encode_bytes(out, text.as_bytes()),
+        AbiValue::Array(values) => {
+            out.extend_from_slice(&word(values.len() as u128));
+            out.extend_from_slice(&encode(values));
+        },
+    }
+}
+
+/// Encodes `values` as an ABI tuple, which is how arguments of functions are encoded, without the selector.
+pub fn encode(values: &[AbiValue]) -> Vec<u8> {
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    for value in values {
+        if value.is_dynamic() {
+            head.extend_from_slice(&word((values.len() * WORD + tail.len()) as u128));
+            encode_value(&mut tail, value);
+        }
+        else {
+            encode_value(&mut head, value);
+        }
+    }
+    head.extend_from_slice(&tail);
+
+    head
+}
+
+/// Reads the word at `at` of `data`.
+fn read_word(data: &[u8], at: usize) -> Option<&[u8]> {
+    data.get(at..at.checked_add(WORD)?)
+}
+
+/// Reads the word at `at` of `data` as an unsigned integer, which must fit in `u128`.
+fn read_u128(data: &[u8], at: usize) -> Option<u128> {
+    let bytes = read_word(data, at)?;
+    if bytes[..WORD - 16].iter().any(|b| *b != 0) {
+        return None;
+    }
+
+    Some(u128::from_be_bytes(bytes[WORD - 16..].try_into().ok()?))
+}
+
+/// Reads the word at `at` of `data` as a length or an offset.
+fn read_usize(data: &[u8], at: usize) -> Option<usize> {
+    usize::try_from(read_u128(data, at)?).ok()
+}
+
+/// Decodes the value of `ty` at `at` of `data`, dynamic values are decoded from their tail.
+fn decode_value(ty: &AbiType, data: &[u8], at: usize) -> Option<AbiValue> {
+    let value = match ty {
+        AbiType::Uint => AbiValue::Uint(read_u128(data, at)?),
+        AbiType::Int => {
+            let bytes = read_word(data, at)?;
+            let value = i128::from_be_bytes(bytes[WORD - 16..].try_into().ok()?);
+            let fill = if value < 0 { 0xff } else { 0x00 };
+            if bytes[..WORD - 16].iter().any(|b| *b != fill) {
+                return None;
+            }
+            AbiValue::Int(value)
+        },
+        AbiType::Address => {
+            let bytes = read_word(data, at)?;
+            if bytes[..WORD - 20].iter().any(|b| *b != 0) {
+                return None;
+            }
+            AbiValue::Address(bytes[WORD - 20..].try_into().ok()?)
+        },
+        AbiType::Bytes => {
+            let len = read_usize(data, at)?;
+            let start = at.checked_add(WORD)?;
+            AbiValue::Bytes(data.get(start..start.checked_add(len)?)?.to_vec())
+        },
+        AbiType::String => {
+            let len = read_usize(data, at)?;
+            let start = at.checked_add(WORD)?;
+            AbiValue::String(String::from_utf8(data.get(start..start.checked_add(len)?)?.to_vec()).ok()?)
+        },
+        AbiType::Array(item) => {
+            let len = read_usize(data, at)?;
+            // Every item takes a word at least, so longer arrays can not be encoded in `data`.
+            if len > data.len() / WORD {
+                return None;
+            }
+            AbiValue::Array(decode_tuple(&vec![item.as_ref().clone(); len], data, at.checked_add(WORD)?)?)
+        },
+    };
+
+    Some(value)
+}
+
+/// Decodes an ABI tuple of `types` starting at `base` of `data`.
+fn decode_tuple(types: &[AbiType], data: &[u8], base: usize) -> Option<Vec<AbiValue>> {
+    let mut values = Vec::new();
+    for (i, ty) in types.iter().enumerate() {
+        let head = base.checked_add(i.checked_mul(WORD)?)?;
+        let at = if ty.is_dynamic() {
+            base.checked_add(read_usize(data, head)?)?
+        }
+        else {
+            head
+        };
+        values.push(decode_value(ty, data, at)?);
+    }
+
+    Some(values)
+}
+
+/// Decodes `data` as an ABI tuple of `types`, e.g. the data returned by an EVM contract in a callback.
+/// Returns `None` if `data` is not a valid encoding of `types`.
+pub fn decode(types: &[AbiType], data: &[u8]) -> Option<Vec<AbiValue>> {
+    decode_tuple(types, data, 0)
+}
+
+/// The Ethereum ABI codec of values of `types`, which can be returned by `PayloadCodecs::get_payload_codec`.
+pub struct AbiCodec {
+    /// Types of the values, in order.
+    pub types: Vec<AbiType>,
+}
+
+impl PayloadCodec<Vec<AbiValue>> for AbiCodec {
+    fn encode(& self, value: &Vec<AbiValue>) -> Result<Vec<u8>, Error> {
+        Ok(encode(value))
+    }
+
+    fn decode(& self, data: &[u8]) -> Result<Vec<AbiValue>, Error> {
+        decode(&self.types, data).ok_or(Error::InvalidEncoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// We test if values are encoded like Solidity `abi.encode`, and decoded back.
+    #[test]
+    fn encode_and_decode_work() {
+        let values = vec![
+            AbiValue::Uint(1),
+            AbiValue::String(String::from("hi")),
+            AbiValue::Array(vec![AbiValue::Int(-1), AbiValue::Int(2)]),
+        ];
+        let data = encode(&values);
+        assert_eq!(data.len(), WORD * 8);
+        assert_eq!(data[WORD * 2 - 1], 0x60);
+        assert_eq!(data[WORD * 3 - 1], 0xa0);
+        assert_eq!(&data[WORD * 4..WORD * 4 + 2], b"hi");
+        assert_eq!(data[WORD * 6], 0xff);
+
+        let types = [AbiType::Uint, AbiType::String, AbiType::Array(Box::new(AbiType::Int))];
+        assert_eq!(decode(&types, &data), Some(values));
+        assert_eq!(decode(&types, &data[..WORD * 7]), None);
+    }
+}
@@ -0,0 +1,269 @@
+use ink::primitives::AccountId;
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use ink::storage::Mapping;
+
+/// An ordered list of cross-chain contracts with their health status.
+///
+/// The helpers send messages through the first available contract, and fall back to the next one if a call fails,
+/// marking the failed contract as unhealthy. An unhealthy contract is available again `recovery_delay` blocks later,
+/// and it is healthy again once a call through it succeeds.
+///
+/// Each cross-chain contract numbers its messages on its own, so two of them can give out the same id.
+/// Messages sent through the list get ids of one namespace across the list instead,
+/// and the contract which handled each message is recorded with the id it gave out,
+/// so responses delivered by it are matched to the message.
+#[ink::storage_item]
+#[derive(Default)]
+pub struct CrossChainContracts {
+    recovery_delay: u32,
+    contracts: Vec<(AccountId, Option<u32>)>,
+    next_id: u128,
+    handled_by: Mapping<(String, u128), (AccountId, u128)>,
+    ids: Mapping<(AccountId, String, u128), u128>,
+}
+
+impl CrossChainContracts {
+    /// Creates the list, unhealthy contracts are tried again `recovery_delay` blocks after they fail.
+    pub fn new(recovery_delay: u32) -> Self {
+        Self {
+            recovery_delay,
+            contracts: Vec::new(),
+            next_id: 0,
+            handled_by: Default::default(),
+            ids: Default::default(),
+        }
+    }
+
+    /// Returns the number of blocks after which an unhealthy contract is tried again.
+    pub fn recovery_delay(& self) -> u32 {
+        self.recovery_delay
+    }
+
+    /// Sets the number of blocks after which an unhealthy contract is tried again.
+    pub fn set_recovery_delay(&mut self, recovery_delay: u32) {
+        self.recovery_delay = recovery_delay;
+    }
+
+    /// Returns the cross-chain contracts in order, with their health status.
+    pub fn contracts(& self) -> Vec<(AccountId, bool)> {
+        self.contracts.iter().map(|(contract, failed_at)| (*contract, failed_at.is_none())).collect()
+    }
+
+    /// Returns the healthy cross-chain contracts in order.
+    pub fn healthy_contracts(& self) -> Vec<AccountId> {
+        self.contracts.iter().filter(|(_, failed_at)| failed_at.is_none()).map(|(contract, _)| *contract).collect()
+    }
+
+    /// Returns the cross-chain contracts which can be tried in order,
+    /// which are the healthy ones, and the unhealthy ones whose recovery delay has passed.
+    pub fn available_contracts(& self) -> Vec<AccountId> {
+        let now = ink::env::block_number::<ink::env::DefaultEnvironment>();
+        self.contracts.iter()
+            .filter(|(_, failed_at)| match failed_at {
+                Some(failed_at) => now >= failed_at.saturating_add(self.recovery_delay),
+                None => true,
+            })
+            .map(|(contract, _)| *contract)
+            .collect()
+    }
+
+    /// Returns if `contract` is one of the cross-chain contracts.
+    pub fn contains(& self, contract: &AccountId) -> bool {
+        self.contracts.iter().any(|(c, _)| c == contract)
+    }
+
+    /// Returns if the list is empty.
+    pub fn is_empty(& self) -> bool {
+        self.contracts.is_empty()
+    }
+
+    /// Appends `contract` to the list as healthy, if it is not in the list.
+    pub fn add(&mut self, contract: AccountId) {
+        if !self.contains(&contract) {
+            self.contracts.push((contract, None));
+        }
+    }
+
+    /// Removes `contract` from the list.
+    pub fn remove(&mut self, contract: &AccountId) {
+        self.contracts.retain(|(c, _)| c != contract);
+    }
+
+    /// Sets the health status of `contract`, an unhealthy one is tried again after the recovery delay.
+    pub fn set_healthy(&mut self, contract: &AccountId, healthy: bool) {
+        let failed_at = if healthy {
+            None
+        }
+        else {
+            Some(ink::env::block_number::<ink::env::DefaultEnvironment>())
+        };
+        for item in self.contracts.iter_mut() {
+            if item.0 == *contract {
+                item.1 = failed_at;
+            }
+        }
+    }
+
+    /// Returns the cross-chain contract which handled the message `id` sent to `chain_name`, and the id it gave out.
+    pub fn handled_by(& self, chain_name: &str, id: u128) -> Option<(AccountId, u128)> {
+        self.handled_by.get((chain_name, id))
+    }
+
+    /// Returns the id of the message sent to `chain_name`, which `contract` gave out the id `contract_id`.
+    pub fn id_of(& self, contract: &AccountId, chain_name: &str, contract_id: u128) -> Option<u128> {
+        self.ids.get((contract, chain_name, contract_id))
+    }
+
+    /// Records that a message sent to `chain_name` is handled by `contract`, which gave out the id `contract_id`,
+    /// and returns the id of the message in the namespace of the list.
+    pub fn record_handled_by(&mut self, chain_name: &str, contract: &AccountId, contract_id: u128) -> u128 {
+        self.next_id = self.next_id + 1;
+        self.handled_by.insert((chain_name, self.next_id), &(*contract, contract_id));
+        self.ids.insert((contract, chain_name, contract_id), &self.next_id);
+
+        self.next_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use payload::message_define::{
+        IContent,
+        IContext,
+        IRequestMessage,
+        ISession,
+        ISQoS,
+    };
+    use crate::cross_chain_helper::{
+        self,
+        CrossChainBase,
+        Error,
+    };
+    use crate::message_status::{
+        MessageStatus,
+        MessageStatusRecord,
+    };
+    use crate::replay_guard::ReplayGuard;
+    use crate::transport::CrossChainTransport;
+    use crate::mock::MockTransport;
+
+    #[derive(Default)]
+    struct MockContract {
+        transport: MockTransport,
+        contracts: CrossChainContracts,
+        record: MessageStatusRecord,
+        guard: ReplayGuard,
+    }
+
+    impl CrossChainBase for MockContract {
+        fn get_replay_guard_mut(&mut self) -> Option<&mut ReplayGuard> {
+            Some(&mut self.guard)
+        }
+
+        fn get_cross_chain_contracts(& self) -> Option<&CrossChainContracts> {
+            Some(&self.contracts)
+        }
+
+        fn get_cross_chain_contracts_mut(&mut self) -> Option<&mut CrossChainContracts> {
+            Some(&mut self.contracts)
+        }
+
+        fn get_message_status_record(& self) -> Option<&MessageStatusRecord> {
+            Some(&self.record)
+        }
+
+        fn get_message_status_record_mut(&mut self) -> Option<&mut MessageStatusRecord> {
+            Some(&mut self.record)
+        }
+
+        fn get_transport(& self) -> &dyn CrossChainTransport {
+            &self.transport
+        }
+    }
+
+    fn response(id: u128) -> IContext {
+        let session = ISession::new(id, 3, Vec::<u8>::new(), Vec::<u8>::new(), Vec::<u8>::new());
+        IContext::new(1, String::from("Ethereum"), ink::prelude::vec![0x01; 20], Vec::<u8>::new(), Vec::<ISQoS>::new(), [0; 32], [0; 4], session)
+    }
+
+    /// We test if unhealthy contracts are skipped in order, and available again after the recovery delay.
+    #[ink::test]
+    fn available_contracts_works() {
+        let first = AccountId::from([0x01; 32]);
+        let second = AccountId::from([0x02; 32]);
+        let mut contracts = CrossChainContracts::new(2);
+        contracts.add(first);
+        contracts.add(second);
+        contracts.add(first);
+        assert_eq!(contracts.available_contracts(), [first, second]);
+
+        contracts.set_healthy(&first, false);
+        assert_eq!(contracts.available_contracts(), [second]);
+        assert!(contracts.contains(&first));
+        ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+        ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+        assert_eq!(contracts.available_contracts(), [first, second]);
+        assert_eq!(contracts.healthy_contracts(), [second]);
+
+        assert_eq!(contracts.record_handled_by("ETHEREUM", &first, 1), 1);
+        assert_eq!(contracts.record_handled_by("ETHEREUM", &second, 1), 2);
+        assert_eq!(contracts.handled_by("ETHEREUM", 1), Some((first, 1)));
+        assert_eq!(contracts.handled_by("ETHEREUM", 2), Some((second, 1)));
+        assert_eq!(contracts.id_of(&second, "ETHEREUM", 1), Some(2));
+        assert_eq!(contracts.id_of(&second, "NEAR", 1), None);
+    }
+
+    /// We test if messages which two cross-chain contracts give the same id get different ids,
+    /// and the responses delivered by each contract are matched to its message.
+    #[ink::test]
+    fn same_ids_from_two_contracts_work() {
+        let first = AccountId::from([0x01; 32]);
+        let second = AccountId::from([0x02; 32]);
+        let mut contract = MockContract::default();
+        contract.contracts.add(first);
+        contract.contracts.add(second);
+        let send = |contract: &mut MockContract| {
+            let content = IContent::new(Vec::<u8>::new(), Vec::<u8>::new(), Vec::<u8>::new());
+            cross_chain_helper::cross_chain_send_message(contract, IRequestMessage::new(String::from("ETHEREUM"), Vec::new(), content))
+        };
+        assert_eq!(send(&mut contract), Ok(1));
+        contract.transport.set_failing(first, true);
+        assert_eq!(send(&mut contract), Ok(2));
+        assert_eq!(contract.contracts.handled_by("ETHEREUM", 1), Some((first, 1)));
+        assert_eq!(contract.contracts.handled_by("ETHEREUM", 2), Some((second, 1)));
+
+        ink::env::test::set_caller::<ink::env::DefaultEnvironment>(second);
+        contract.transport.set_context(Some(response(1)));
+        assert_eq!(cross_chain_helper::cross_chain_receive(&mut contract).map(|context| context.session.id), Ok(2));
+        assert_eq!(contract.record.get("ETHEREUM", 1), Some(MessageStatus::Sent));
+        assert_eq!(contract.record.get("ETHEREUM", 2), Some(MessageStatus::Responded));
+
+        ink::env::test::set_caller::<ink::env::DefaultEnvironment>(first);
+        contract.transport.set_context(Some(response(1)));
+        assert_eq!(cross_chain_helper::cross_chain_receive(&mut contract).map(|context| context.session.id), Ok(1));
+        assert_eq!(contract.record.get("ETHEREUM", 1), Some(MessageStatus::Responded));
+    }
+
+    /// We test if SQoS is configured in the other contracts when one of them fails, which is marked as unhealthy.
+    #[ink::test]
+    fn configure_sqos_skips_failed_contracts() {
+        let first = AccountId::from([0x01; 32]);
+        let second = AccountId::from([0x02; 32]);
+        let account_id = AccountId::from([0x03; 32]);
+        let mut contract = MockContract {
+            contracts: CrossChainContracts::new(10),
+            ..Default::default()
+        };
+        contract.contracts.add(first);
+        contract.contracts.add(second);
+        contract.transport.set_failing(first, true);
+        assert_eq!(cross_chain_helper::remove_sqos(&mut contract, account_id), Ok(()));
+        assert_eq!(contract.contracts.healthy_contracts(), [second]);
+
+        contract.transport.set_failing(second, true);
+        assert_eq!(cross_chain_helper::remove_sqos(&mut contract, account_id), Err(Error::CallFailed));
+        assert_eq!(cross_chain_helper::remove_sqos(&mut contract, account_id), Err(Error::NoHealthyContract));
+    }
+}
@@ -1,19 +1,66 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub mod cross_chain_helper;
+pub mod transport;
+pub mod failover;
+pub mod timelock;
+pub mod message_status;
+pub mod retry;
+pub mod dead_letter;
+pub mod result_store;
+pub mod remote_address;
+pub mod chain_registry;
+pub mod codec;
+pub mod limits;
+pub mod context;
+pub mod replay_guard;
+pub mod ordered_channel;
+pub mod aggregator;
+pub mod routing;
+pub mod chunking;
+#[cfg(feature = "eth-abi")]
+pub mod eth_abi;
+#[cfg(feature = "near-codec")]
+pub mod near_codec;
+#[cfg(feature = "compression")]
+pub mod compression;
+#[cfg(test)]
+mod mock;
 pub use crate::ink_sdk::{
     Ownable,
     MultiDestContracts,
     CrossChainSQoS,
+    ManageDeadLetters,
+    ManageChains,
+    Pausable,
+    PauseState,
+    Direction,
 };
+#[cfg(feature = "near-codec")]
+pub use crate::ink_sdk::DestEncodings;
 
 #[ink::contract]
 mod ink_sdk {
     use ink::prelude::string::String;
     use ink::prelude::vec::Vec;
+    use ink::storage::Mapping;
     use payload::message_define::{
         ISQoS,
     };
+    use crate::dead_letter::DeadLetter;
+    use crate::chain_registry::ChainInfo;
+    #[cfg(feature = "near-codec")]
+    use crate::codec::PayloadEncoding;
+
+    /// Direction of cross-chain messages.
+    #[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Direction {
+        /// Messages received from other chains.
+        Inbound,
+        /// Messages sent to other chains.
+        Outbound,
+    }
 
     /// This trait can be used when a contract need access control.
     #[ink::trait_definition]
@@ -41,17 +88,31 @@ mod ink_sdk {
         fn register_dest_contract(&mut self, chain_name: String, action: String, contract: Vec<u8>, dest_action: Vec<u8>) -> Result<(), u8>;
     }
 
+    /// This trait can be used when a contract sends data of other encodings than `MessagePayload` to destination contracts,
+    /// e.g. JSON or Borsh to Near contracts. Chain names should be normalized like in `MultiDestContracts`.
+    #[cfg(feature = "near-codec")]
+    #[ink::trait_definition]
+    pub trait DestEncodings {
+        /// Returns the encoding of the data exchanged with the destination contract, `PayloadEncoding::Scale` if it is not set.
+        #[ink(message)]
+        fn get_dest_encoding(& self, chain_name: String, action: String) -> PayloadEncoding;
+
+        /// Sets the encoding of the data exchanged with the destination contract.
+        #[ink(message)]
+        fn set_dest_encoding(&mut self, chain_name: String, action: String, encoding: PayloadEncoding) -> Result<(), u8>;
+    }
+
     /// This trait can be used when a contract has custom SQoS demands.
     #[ink::trait_definition]
     pub trait CrossChainSQoS {
         /// Inserts one SQoS item.
         /// If the item exists, it will be replaced.
         #[ink(message)]
-        fn set_sqos(&mut self, sqos_item: ISQoS);
+        fn set_sqos(&mut self, sqos_item: ISQoS) -> Result<(), u8>;
 
         /// Removes one SQoS item.
         #[ink(message)]
-        fn remove_sqos(&mut self);
+        fn remove_sqos(&mut self) -> Result<(), u8>;
 
         // /// Clear all SQoS items.
         // #[ink(message)]
@@ -66,6 +127,102 @@ mod ink_sdk {
         fn get_sqos(& self) -> Option<ISQoS>;
     }
 
+    /// This trait can be used when a contract needs to halt cross-chain messages in emergencies.
+    #[ink::trait_definition]
+    pub trait Pausable {
+        /// Returns if messages in `direction` are paused.
+        /// If `chain_name` is `None`, returns if they are paused for all chains.
+        #[ink(message)]
+        fn paused(& self, direction: Direction, chain_name: Option<String>) -> bool;
+
+        /// Pauses messages in `direction` from or to `chain_name`.
+        /// If `chain_name` is `None`, messages of all chains will be paused.
+        #[ink(message)]
+        fn pause(&mut self, direction: Direction, chain_name: Option<String>) -> Result<(), u8>;
+
+        /// Unpauses messages in `direction` from or to `chain_name`.
+        /// If `chain_name` is `None`, the pause flag for all chains will be cleared.
+        #[ink(message)]
+        fn unpause(&mut self, direction: Direction, chain_name: Option<String>) -> Result<(), u8>;
+    }
+
+    /// This trait can be used when a contract keeps inbound messages whose handler fails.
+    #[ink::trait_definition]
+    pub trait ManageDeadLetters {
+        /// Returns the dead letter `id`.
+        #[ink(message)]
+        fn get_dead_letter(& self, id: u128) -> Option<DeadLetter>;
+
+        /// Handles the dead letter `id` again, it is removed if the handler succeeds.
+        #[ink(message)]
+        fn replay_dead_letter(&mut self, id: u128) -> Result<(), u8>;
+
+        /// Discards the dead letter `id`.
+        #[ink(message)]
+        fn discard_dead_letter(&mut self, id: u128) -> Result<(), u8>;
+    }
+
+    /// This trait can be used when a contract keeps a registry of chains it talks to.
+    #[ink::trait_definition]
+    pub trait ManageChains {
+        /// Returns the information of `chain_name`, which is normalized first.
+        #[ink(message)]
+        fn get_chain(& self, chain_name: String) -> Option<ChainInfo>;
+
+        /// Registers `chain_name` with `info`, the numeric id must be unique.
+        #[ink(message)]
+        fn register_chain(&mut self, chain_name: String, info: ChainInfo) -> Result<(), u8>;
+
+        /// Unregisters `chain_name`.
+        #[ink(message)]
+        fn unregister_chain(&mut self, chain_name: String) -> Result<(), u8>;
+    }
+
+    /// Pause flags of a contract, which can be used to implement `Pausable`.
+    /// Expose it with `CrossChainBase::get_pause_state`, and the helpers will check it automatically.
+    #[ink::storage_item]
+    #[derive(Default)]
+    pub struct PauseState {
+        inbound: bool,
+        outbound: bool,
+        chains: Mapping<(Direction, String), bool>,
+    }
+
+    impl PauseState {
+        /// Returns if messages in `direction` are paused for all chains.
+        pub fn is_paused_for_all(& self, direction: Direction) -> bool {
+            match direction {
+                Direction::Inbound => self.inbound,
+                Direction::Outbound => self.outbound,
+            }
+        }
+
+        /// Returns if messages in `direction` from or to `chain_name` are paused.
+        pub fn is_paused(& self, direction: Direction, chain_name: &str) -> bool {
+            self.is_paused_for_all(direction) || self.chains.get((direction, chain_name)).unwrap_or(false)
+        }
+
+        /// Sets the pause flag in `direction` for `chain_name`, or for all chains if `chain_name` is `None`.
+        pub fn set_paused(&mut self, direction: Direction, chain_name: Option<String>, paused: bool) {
+            match chain_name {
+                Some(chain_name) => {
+                    if paused {
+                        self.chains.insert((direction, chain_name), &true);
+                    }
+                    else {
+                        self.chains.remove((direction, chain_name));
+                    }
+                },
+                None => {
+                    match direction {
+                        Direction::Inbound => self.inbound = paused,
+                        Direction::Outbound => self.outbound = paused,
+                    }
+                },
+            }
+        }
+    }
+
     /// Defines the storage of your contract.
     /// Add new fields to the below struct in order
     /// to add new static storage fields to your contract.
@@ -0,0 +1,143 @@
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use ink::storage::Mapping;
+use scale::DecodeAll;
+use payload::message_protocol::{
+    MsgDetail,
+    MessagePayload,
+};
+use crate::cross_chain_helper::Error;
+use crate::chain_registry;
+
+/// Limits of the payloads sent to a chain, `None` means unlimited.
+#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Copy, Clone, Default)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct PayloadLimits {
+    /// Max size of the encoded data in bytes.
+    pub max_bytes: Option<u32>,
+    /// Max number of payload items.
+    pub max_items: Option<u32>,
+    /// Max length in bytes of item names and string values.
+    pub max_string_len: Option<u32>,
+}
+
+impl PayloadLimits {
+    /// Returns `Error::PayloadTooLarge` if `data` exceeds the max size.
+    pub fn check_size(& self, data: &[u8]) -> Result<(), Error> {
+        match self.max_bytes {
+            Some(max_bytes) if data.len() > max_bytes as usize => Err(Error::PayloadTooLarge),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns `Error::TooManyItems` if there are more items than the max number,
+    /// or `Error::StringTooLong` if an item name or a string value is longer than the max length.
+    pub fn check_items(& self, items: &[(String, MsgDetail)]) -> Result<(), Error> {
+        if let Some(max_items) = self.max_items {
+            if items.len() > max_items as usize {
+                return Err(Error::TooManyItems);
+            }
+        }
+
+        if let Some(max_string_len) = self.max_string_len {
+            let max_string_len = max_string_len as usize;
+            for (name, value) in items {
+                let too_long = name.len() > max_string_len || match value {
+                    MsgDetail::InkString(value) => value.len() > max_string_len,
+                    MsgDetail::InkStringArray(values) => values.iter().any(|value| value.len() > max_string_len),
+                    _ => false,
+                };
+                if too_long {
+                    return Err(Error::StringTooLong);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error if `data` exceeds the max size, or if it is a `MessagePayload` whose items exceed the limits of items.
+    /// The limits of items only apply to SCALE encoded `MessagePayload` data.
+    /// Data of other encodings, e.g. JSON for Near or Ethereum ABI, is only checked against the max size,
+    /// so the helpers which encode it check the items before encoding them.
+    pub fn check_data(& self, data: &[u8]) -> Result<(), Error> {
+        self.check_size(data)?;
+        if self.max_items.is_none() && self.max_string_len.is_none() {
+            return Ok(());
+        }
+
+        match payload_items(data) {
+            Some(items) => self.check_items(&items),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the items of `data`, if it is exactly a SCALE encoded `MessagePayload`.
+pub fn payload_items(data: &[u8]) -> Option<Vec<(String, MsgDetail)>> {
+    let msg_payload = MessagePayload::decode_all(&mut &data[..]).ok()?;
+
+    Some(items_of(&msg_payload))
+}
+
+/// Returns the items of `msg_payload`, to check them before it is encoded.
+pub fn items_of(msg_payload: &MessagePayload) -> Vec<(String, MsgDetail)> {
+    msg_payload.items.iter().map(|item| (item.n.clone(), item.tv.clone())).collect()
+}
+
+/// Payload limits per destination chain, with a default for other chains.
+/// It can be used to implement `CrossChainBase::get_payload_limits`.
+#[ink::storage_item]
+#[derive(Default)]
+pub struct PayloadLimitsConfig {
+    default: Option<PayloadLimits>,
+    chains: Mapping<String, PayloadLimits>,
+}
+
+impl PayloadLimitsConfig {
+    /// Returns the limits of `chain_name`, or the default ones if it has none.
+    pub fn get(& self, chain_name: &str) -> Option<PayloadLimits> {
+        self.chains.get(chain_registry::normalize(chain_name)).or(self.default)
+    }
+
+    /// Sets the limits of `chain_name`, which is normalized, or the default ones if `chain_name` is `None`.
+    /// `None` limits are removed.
+    pub fn set(&mut self, chain_name: Option<String>, limits: Option<PayloadLimits>) {
+        match (chain_name.map(|chain_name| chain_registry::normalize(&chain_name)), limits) {
+            (Some(chain_name), Some(limits)) => {
+                self.chains.insert(chain_name, &limits);
+            },
+            (Some(chain_name), None) => self.chains.remove(chain_name),
+            (None, limits) => self.default = limits,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// We test if the limits are checked, and chains fall back to the default limits.
+    #[ink::test]
+    fn payload_limits_work() {
+        let limits = PayloadLimits { max_bytes: Some(4), max_items: Some(1), max_string_len: Some(8) };
+        assert_eq!(limits.check_size(&[0; 4]), Ok(()));
+        assert_eq!(limits.check_size(&[0; 5]), Err(Error::PayloadTooLarge));
+        let greeting = (String::from("greeting"), MsgDetail::InkStringArray(ink::prelude::vec![String::from("Hello, Near")]));
+        assert_eq!(limits.check_items(&[greeting.clone(), greeting.clone()]), Err(Error::TooManyItems));
+        assert_eq!(limits.check_items(&[greeting.clone()]), Err(Error::StringTooLong));
+
+        let unlimited_size = PayloadLimits { max_bytes: None, ..limits };
+        let mut msg_payload = MessagePayload::new();
+        msg_payload.push_item(greeting.0, greeting.1);
+        assert_eq!(unlimited_size.check_data(&msg_payload.to_bytes()), Err(Error::StringTooLong));
+        assert_eq!(unlimited_size.check_data(br#"{"greeting":"Hello, Near"}"#), Ok(()));
+
+        let mut config = PayloadLimitsConfig::default();
+        config.set(None, Some(limits));
+        config.set(Some(String::from("Near")), Some(PayloadLimits::default()));
+        assert_eq!(config.get("NEAR"), Some(PayloadLimits::default()));
+        assert_eq!(items_of(&msg_payload).len(), 1);
+        assert_eq!(config.get("ETHEREUM"), Some(limits));
+    }
+}
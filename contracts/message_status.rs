@@ -0,0 +1,54 @@
+use ink::prelude::string::String;
+use ink::storage::Mapping;
+
+/// Status of a message sent to another chain.
+#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum MessageStatus {
+    /// The message is recorded by the cross-chain contract, no response or error has arrived.
+    Sent,
+    /// The response of the message has arrived.
+    Responded,
+    /// A local error of the message has arrived, session type `104`.
+    LocalError,
+    /// A remote error of the message has arrived, session type `105`.
+    RemoteError,
+}
+
+impl MessageStatus {
+    /// Returns the status of the sent message an inbound message of `session_type` reports, if any.
+    pub fn from_session_type(session_type: u8) -> Option<Self> {
+        match session_type {
+            3 => Some(MessageStatus::Responded),
+            104 => Some(MessageStatus::LocalError),
+            105 => Some(MessageStatus::RemoteError),
+            _ => None,
+        }
+    }
+}
+
+/// Local status records of sent messages, keyed by destination chain and message id.
+/// Expose it with `CrossChainBase::get_message_status_record`, and the helpers will update it
+/// when messages are sent, and when responses or errors arrive.
+#[ink::storage_item]
+#[derive(Default)]
+pub struct MessageStatusRecord {
+    statuses: Mapping<(String, u128), MessageStatus>,
+}
+
+impl MessageStatusRecord {
+    /// Returns the status of the message `id` sent to `chain_name`.
+    pub fn get(& self, chain_name: &str, id: u128) -> Option<MessageStatus> {
+        self.statuses.get((chain_name, id))
+    }
+
+    /// Sets the status of the message `id` sent to `chain_name`.
+    pub fn set(&mut self, chain_name: &str, id: u128, status: MessageStatus) {
+        self.statuses.insert((chain_name, id), &status);
+    }
+
+    /// Removes the status of the message `id` sent to `chain_name`.
+    pub fn remove(&mut self, chain_name: &str, id: u128) {
+        self.statuses.remove((chain_name, id));
+    }
+}
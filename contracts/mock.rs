@@ -0,0 +1,90 @@
+use core::cell::RefCell;
+use ink::primitives::AccountId;
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use payload::message_define::{
+    ISentMessage,
+    ISQoS,
+    IContext,
+};
+use crate::cross_chain_helper::Error;
+use crate::transport::CrossChainTransport;
+
+/// A transport which numbers and records the messages sent per cross-chain contract, instead of calling it.
+/// It is shared by the tests of the helpers.
+#[derive(Default)]
+pub struct MockTransport {
+    sent: RefCell<Vec<(AccountId, u128)>>,
+    messages: RefCell<Vec<(AccountId, u128, ISentMessage)>>,
+    failing: RefCell<Vec<AccountId>>,
+    context: RefCell<Option<IContext>>,
+}
+
+impl MockTransport {
+    /// Makes the calls to `cross_chain` fail, or succeed again.
+    pub fn set_failing(& self, cross_chain: AccountId, failing: bool) {
+        let mut contracts = self.failing.borrow_mut();
+        contracts.retain(|contract| *contract != cross_chain);
+        if failing {
+            contracts.push(cross_chain);
+        }
+    }
+
+    /// Sets the context of the next message delivered, it is returned once.
+    pub fn set_context(& self, context: Option<IContext>) {
+        *self.context.borrow_mut() = context;
+    }
+
+    /// Returns `Error::CallFailed` if the calls to `cross_chain` fail.
+    fn check(& self, cross_chain: AccountId) -> Result<(), Error> {
+        if self.failing.borrow().contains(&cross_chain) {
+            return Err(Error::CallFailed);
+        }
+
+        Ok(())
+    }
+}
+
+impl CrossChainTransport for MockTransport {
+    fn send_message(& self, cross_chain: AccountId, message: ISentMessage) -> Result<u128, Error> {
+        self.check(cross_chain)?;
+        let mut sent = self.sent.borrow_mut();
+        let id = match sent.iter_mut().find(|(contract, _)| *contract == cross_chain) {
+            Some((_, count)) => {
+                *count = *count + 1;
+                *count
+            },
+            None => {
+                sent.push((cross_chain, 1));
+                1
+            },
+        };
+        self.messages.borrow_mut().push((cross_chain, id, message));
+
+        Ok(id)
+    }
+
+    fn get_context(& self, _cross_chain: AccountId) -> Option<IContext> {
+        self.context.borrow_mut().take()
+    }
+
+    fn get_sqos(& self, cross_chain: AccountId, _account_id: AccountId) -> Result<Option<ISQoS>, Error> {
+        self.check(cross_chain)?;
+        Ok(None)
+    }
+
+    fn set_sqos(& self, cross_chain: AccountId, _sqos: ISQoS, _account_id: AccountId) -> Result<(), Error> {
+        self.check(cross_chain)
+    }
+
+    fn remove_sqos(& self, cross_chain: AccountId, _account_id: AccountId) -> Result<(), Error> {
+        self.check(cross_chain)
+    }
+
+    fn get_sent_message(& self, cross_chain: AccountId, _to_chain: String, id: u128) -> Result<Option<ISentMessage>, Error> {
+        self.check(cross_chain)?;
+        Ok(self.messages.borrow().iter()
+            .find(|(contract, sent_id, _)| *contract == cross_chain && *sent_id == id)
+            .map(|(_, _, message)| message.clone()))
+    }
+}
@@ -0,0 +1,725 @@
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use scale::Decode;
+use payload::message_define::{
+    ISQoS,
+    IContent,
+    IContext,
+    IRequestMessage,
+};
+use payload::message_protocol::{
+    MsgDetail,
+    MessagePayload,
+};
+use crate::{
+    MultiDestContracts,
+    DestEncodings,
+};
+use crate::codec::{
+    PayloadCodec,
+    PayloadEncoding,
+};
+use crate::cross_chain_helper::{
+    self,
+    CrossChainBase,
+    Error,
+};
+
+/// Types of the fields decoded from Near data, each one matches a `MsgDetail` variant.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum NearType {
+    String,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    StringArray,
+    U8Array,
+    U16Array,
+    U32Array,
+    U64Array,
+    U128Array,
+    I8Array,
+    I16Array,
+    I32Array,
+    I64Array,
+    I128Array,
+}
+
+/// An integer of a field.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum Num {
+    Unsigned(u128),
+    Signed(i128),
+}
+
+/// A field in the form shared by Borsh and JSON, integers carry their width in bytes.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Field {
+    Num(Num, usize),
+    Str(String),
+    Nums(Vec<Num>, usize),
+    Strs(Vec<String>),
+}
+
+/// Returns the shape of `ty`: the width and signedness of its integers, if it is an array, and if it is a string.
+fn shape_of(ty: NearType) -> (usize, bool, bool, bool) {
+    match ty {
+        NearType::String => (0, false, false, true),
+        NearType::U8 => (1, false, false, false),
+        NearType::U16 => (2, false, false, false),
+        NearType::U32 => (4, false, false, false),
+        NearType::U64 => (8, false, false, false),
+        NearType::U128 => (16, false, false, false),
+        NearType::I8 => (1, true, false, false),
+        NearType::I16 => (2, true, false, false),
+        NearType::I32 => (4, true, false, false),
+        NearType::I64 => (8, true, false, false),
+        NearType::I128 => (16, true, false, false),
+        NearType::StringArray => (0, false, true, true),
+        NearType::U8Array => (1, false, true, false),
+        NearType::U16Array => (2, false, true, false),
+        NearType::U32Array => (4, false, true, false),
+        NearType::U64Array => (8, false, true, false),
+        NearType::U128Array => (16, false, true, false),
+        NearType::I8Array => (1, true, true, false),
+        NearType::I16Array => (2, true, true, false),
+        NearType::I32Array => (4, true, true, false),
+        NearType::I64Array => (8, true, true, false),
+        NearType::I128Array => (16, true, true, false),
+    }
+}
+
+/// Converts a payload item to a field, returns `None` for types which are not supported.
+fn field_of(detail: &MsgDetail) -> Option<Field> {
+    let unsigned = |values: Vec<u128>| values.into_iter().map(Num::Unsigned).collect::<Vec<Num>>();
+    let signed = |values: Vec<i128>| values.into_iter().map(Num::Signed).collect::<Vec<Num>>();
+    let field = match detail {
+        MsgDetail::InkString(value) => Field::Str(value.clone()),
+        MsgDetail::InkU8(value) => Field::Num(Num::Unsigned(*value as u128), 1),
+        MsgDetail::InkU16(value) => Field::Num(Num::Unsigned(*value as u128), 2),
+        MsgDetail::InkU32(value) => Field::Num(Num::Unsigned(*value as u128), 4),
+        MsgDetail::InkU64(value) => Field::Num(Num::Unsigned(*value as u128), 8),
+        MsgDetail::InkU128(value) => Field::Num(Num::Unsigned(*value), 16),
+        MsgDetail::InkI8(value) => Field::Num(Num::Signed(*value as i128), 1),
+        MsgDetail::InkI16(value) => Field::Num(Num::Signed(*value as i128), 2),
+        MsgDetail::InkI32(value) => Field::Num(Num::Signed(*value as i128), 4),
+        MsgDetail::InkI64(value) => Field::Num(Num::Signed(*value as i128), 8),
+        MsgDetail::InkI128(value) => Field::Num(Num::Signed(*value), 16),
+        MsgDetail::InkStringArray(values) => Field::Strs(values.clone()),
+        MsgDetail::InkU8Array(values) => Field::Nums(unsigned(values.iter().map(|v| *v as u128).collect()), 1),
+        MsgDetail::InkU16Array(values) => Field::Nums(unsigned(values.iter().map(|v| *v as u128).collect()), 2),
+        MsgDetail::InkU32Array(values) => Field::Nums(unsigned(values.iter().map(|v| *v as u128).collect()), 4),
+        MsgDetail::InkU64Array(values) => Field::Nums(unsigned(values.iter().map(|v| *v as u128).collect()), 8),
+        MsgDetail::InkU128Array(values) => Field::Nums(unsigned(values.clone()), 16),
+        MsgDetail::InkI8Array(values) => Field::Nums(signed(values.iter().map(|v| *v as i128).collect()), 1),
+        MsgDetail::InkI16Array(values) => Field::Nums(signed(values.iter().map(|v| *v as i128).collect()), 2),
+        MsgDetail::InkI32Array(values) => Field::Nums(signed(values.iter().map(|v| *v as i128).collect()), 4),
+        MsgDetail::InkI64Array(values) => Field::Nums(signed(values.iter().map(|v| *v as i128).collect()), 8),
+        MsgDetail::InkI128Array(values) => Field::Nums(signed(values.clone()), 16),
+        #[allow(unreachable_patterns)]
+        _ => return None,
+    };
+
+    Some(field)
+}
+
+/// Converts a field decoded as `ty` back to a payload item, returns `None` if an integer is out of range.
+fn detail_of(ty: NearType, field: Field) -> Option<MsgDetail> {
+    let u = |num: &Num| match num { Num::Unsigned(value) => Some(*value), Num::Signed(value) => u128::try_from(*value).ok() };
+    let i = |num: &Num| match num { Num::Signed(value) => Some(*value), Num::Unsigned(value) => i128::try_from(*value).ok() };
+    let detail = match (ty, field) {
+        (NearType::String, Field::Str(value)) => MsgDetail::InkString(value),
+        (NearType::U8, Field::Num(num, _)) => MsgDetail::InkU8(u8::try_from(u(&num)?).ok()?),
+        (NearType::U16, Field::Num(num, _)) => MsgDetail::InkU16(u16::try_from(u(&num)?).ok()?),
+        (NearType::U32, Field::Num(num, _)) => MsgDetail::InkU32(u32::try_from(u(&num)?).ok()?),
+        (NearType::U64, Field::Num(num, _)) => MsgDetail::InkU64(u64::try_from(u(&num)?).ok()?),
+        (NearType::U128, Field::Num(num, _)) => MsgDetail::InkU128(u(&num)?),
+        (NearType::I8, Field::Num(num, _)) => MsgDetail::InkI8(i8::try_from(i(&num)?).ok()?),
+        (NearType::I16, Field::Num(num, _)) => MsgDetail::InkI16(i16::try_from(i(&num)?).ok()?),
+        (NearType::I32, Field::Num(num, _)) => MsgDetail::InkI32(i32::try_from(i(&num)?).ok()?),
+        (NearType::I64, Field::Num(num, _)) => MsgDetail::InkI64(i64::try_from(i(&num)?).ok()?),
+        (NearType::I128, Field::Num(num, _)) => MsgDetail::InkI128(i(&num)?),
+        (NearType::StringArray, Field::Strs(values)) => MsgDetail::InkStringArray(values),
+        (NearType::U8Array, Field::Nums(nums, _)) => MsgDetail::InkU8Array(nums.iter().map(|n| u8::try_from(u(n)?).ok()).collect::<Option<_>>()?),
+        (NearType::U16Array, Field::Nums(nums, _)) => MsgDetail::InkU16Array(nums.iter().map(|n| u16::try_from(u(n)?).ok()).collect::<Option<_>>()?),
+        (NearType::U32Array, Field::Nums(nums, _)) => MsgDetail::InkU32Array(nums.iter().map(|n| u32::try_from(u(n)?).ok()).collect::<Option<_>>()?),
+        (NearType::U64Array, Field::Nums(nums, _)) => MsgDetail::InkU64Array(nums.iter().map(|n| u64::try_from(u(n)?).ok()).collect::<Option<_>>()?),
+        (NearType::U128Array, Field::Nums(nums, _)) => MsgDetail::InkU128Array(nums.iter().map(u).collect::<Option<_>>()?),
+        (NearType::I8Array, Field::Nums(nums, _)) => MsgDetail::InkI8Array(nums.iter().map(|n| i8::try_from(i(n)?).ok()).collect::<Option<_>>()?),
+        (NearType::I16Array, Field::Nums(nums, _)) => MsgDetail::InkI16Array(nums.iter().map(|n| i16::try_from(i(n)?).ok()).collect::<Option<_>>()?),
+        (NearType::I32Array, Field::Nums(nums, _)) => MsgDetail::InkI32Array(nums.iter().map(|n| i32::try_from(i(n)?).ok()).collect::<Option<_>>()?),
+        (NearType::I64Array, Field::Nums(nums, _)) => MsgDetail::InkI64Array(nums.iter().map(|n| i64::try_from(i(n)?).ok()).collect::<Option<_>>()?),
+        (NearType::I128Array, Field::Nums(nums, _)) => MsgDetail::InkI128Array(nums.iter().map(i).collect::<Option<_>>()?),
+        _ => return None,
+    };
+
+    Some(detail)
+}
+
+/// Appends `num` as a little-endian integer of `width` bytes.
+fn borsh_num(out: &mut Vec<u8>, num: Num, width: usize) {
+    match num {
+        Num::Unsigned(value) => out.extend_from_slice(&value.to_le_bytes()[..width]),
+        Num::Signed(value) => out.extend_from_slice(&value.to_le_bytes()[..width]),
+    }
+}
+
+/// Appends `text` with its `u32` length.
+fn borsh_str(out: &mut Vec<u8>, text: &str) {
+    out.extend_from_slice(&(text.len() as u32).to_le_bytes());
+    out.extend_from_slice(text.as_bytes());
+}
+
+/// Encodes `values` with Borsh, as the fields of a struct in order.
+/// Returns `None` if a value is not supported.
+pub fn borsh_encode(values: &[MsgDetail]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    for value in values {
+        match field_of(value)? {
+            Field::Num(num, width) => borsh_num(&mut out, num, width),
+            Field::Str(text) => borsh_str(&mut out, &text),
+            Field::Nums(nums, width) => {
+                out.extend_from_slice(&(nums.len() as u32).to_le_bytes());
+                for num in nums {
+                    borsh_num(&mut out, num, width);
+                }
+            },
+            Field::Strs(texts) => {
+                out.extend_from_slice(&(texts.len() as u32).to_le_bytes());
+                for text in texts {
+                    borsh_str(&mut out, &text);
+                }
+            },
+        }
+    }
+
+    Some(out)
+}
+
+/// A cursor over Borsh encoded data.
+struct BorshReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> BorshReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.data.len() {
+            return None;
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(head)
+    }
+
+    fn len(&mut self) -> Option<usize> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?) as usize)
+    }
+
+    fn num(&mut self, width: usize, signed: bool) -> Option<Num> {
+        let bytes = self.take(width)?;
+        let fill = if signed && bytes[width - 1] & 0x80 != 0 { 0xff } else { 0x00 };
+        let mut wide = [fill; 16];
+        wide[..width].copy_from_slice(bytes);
+        if signed {
+            Some(Num::Signed(i128::from_le_bytes(wide)))
+        }
+        else {
+            Some(Num::Unsigned(u128::from_le_bytes(wide)))
+        }
+    }
+
+    fn str(&mut self) -> Option<String> {
+        let len = self.len()?;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+}
+
+/// Decodes Borsh encoded `data` as the fields of a struct of `types`, e.g. the data returned by a Near contract.
+/// Returns `None` if `data` is not a valid encoding of `types`, or has trailing bytes.
+pub fn borsh_decode(types: &[NearType], data: &[u8]) -> Option<Vec<MsgDetail>> {
+    let mut reader = BorshReader { data };
+    let mut values = Vec::new();
+    for ty in types {
+        let (width, signed, array, string) = shape_of(*ty);
+        let field = match (array, string) {
+            (false, true) => Field::Str(reader.str()?),
+            (false, false) => Field::Num(reader.num(width, signed)?, width),
+            (true, true) => {
+                let len = reader.len()?;
+                let mut texts = Vec::new();
+                for _ in 0..len {
+                    texts.push(reader.str()?);
+                }
+                Field::Strs(texts)
+            },
+            (true, false) => {
+                let len = reader.len()?;
+                if len.checked_mul(width)? > reader.data.len() {
+                    return None;
+                }
+                let mut nums = Vec::new();
+                for _ in 0..len {
+                    nums.push(reader.num(width, signed)?);
+                }
+                Field::Nums(nums, width)
+            },
+        };
+        values.push(detail_of(*ty, field)?);
+    }
+    if !reader.data.is_empty() {
+        return None;
+    }
+
+    Some(values)
+}
+
+/// Appends `num` in JSON, integers wider than 32 bits are strings, as `U64` and `U128` of the Near SDK.
+fn json_num(out: &mut String, num: Num, width: usize) {
+    let text = match num {
+        Num::Unsigned(value) => ink::prelude::format!("{}", value),
+        Num::Signed(value) => ink::prelude::format!("{}", value),
+    };
+    if width > 4 {
+        out.push('"');
+        out.push_str(&text);
+        out.push('"');
+    }
+    else {
+        out.push_str(&text);
+    }
+}
+
+/// Appends `text` as a JSON string.
+fn json_str(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&ink::prelude::format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Encodes `fields` as a JSON object, which is how Near contracts take arguments by default.
+/// Returns `None` if a value is not supported.
+pub fn json_encode(fields: &[(String, MsgDetail)]) -> Option<String> {
+    let mut out = String::from("{");
+    for (i, (name, value)) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        json_str(&mut out, name);
+        out.push(':');
+        match field_of(value)? {
+            Field::Num(num, width) => json_num(&mut out, num, width),
+            Field::Str(text) => json_str(&mut out, &text),
+            Field::Nums(nums, width) => {
+                out.push('[');
+                for (j, num) in nums.into_iter().enumerate() {
+                    if j > 0 {
+                        out.push(',');
+                    }
+                    json_num(&mut out, num, width);
+                }
+                out.push(']');
+            },
+            Field::Strs(texts) => {
+                out.push('[');
+                for (j, text) in texts.iter().enumerate() {
+                    if j > 0 {
+                        out.push(',');
+                    }
+                    json_str(&mut out, text);
+                }
+                out.push(']');
+            },
+        }
+    }
+    out.push('}');
+
+    Some(out)
+}
+
+/// Max nesting depth of the JSON values skipped by the decoder, so nested data from other chains can not exhaust the stack.
+const MAX_JSON_DEPTH: usize = 32;
+
+/// A cursor over JSON text, which only reads what the decoder needs.
+struct JsonReader<'a> {
+    text: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> JsonReader<'a> {
+    fn skip_whitespaces(&mut self) {
+        while self.pos < self.text.len() && matches!(self.text[self.pos], b' ' | b'\t' | b'\n' | b'\r') {
+            self.pos = self.pos + 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespaces();
+        self.text.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, c: u8) -> Option<()> {
+        if self.peek()? != c {
+            return None;
+        }
+        self.pos = self.pos + 1;
+        Some(())
+    }
+
+    /// Reads the items of an array or the members of an object until `end`, calling `item` for each one.
+    fn list(&mut self, end: u8, mut item: impl FnMut(&mut Self) -> Option<()>) -> Option<()> {
+        if self.peek()? == end {
+            self.pos = self.pos + 1;
+            return Some(());
+        }
+        loop {
+            item(self)?;
+            match self.peek()? {
+                b',' => self.pos = self.pos + 1,
+                c if c == end => {
+                    self.pos = self.pos + 1;
+                    return Some(());
+                },
+                _ => return None,
+            }
+        }
+    }
+
+    fn str(&mut self) -> Option<String> {
+        self.expect(b'"')?;
+        let mut bytes = Vec::new();
+        loop {
+            let c = *self.text.get(self.pos)?;
+            self.pos = self.pos + 1;
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = *self.text.get(self.pos)?;
+                    self.pos = self.pos + 1;
+                    match escaped {
+                        b'"' | b'\\' | b'/' => bytes.push(escaped),
+                        b'n' => bytes.push(b'\n'),
+                        b'r' => bytes.push(b'\r'),
+                        b't' => bytes.push(b'\t'),
+                        b'b' => bytes.push(0x08),
+                        b'f' => bytes.push(0x0c),
+                        b'u' => {
+                            let hex = core::str::from_utf8(self.text.get(self.pos..self.pos + 4)?).ok()?;
+                            self.pos = self.pos + 4;
+                            let c = char::from_u32(u32::from_str_radix(hex, 16).ok()?)?;
+                            let mut buf = [0u8; 4];
+                            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                        },
+                        _ => return None,
+                    }
+                },
+                _ => bytes.push(c),
+            }
+        }
+
+        String::from_utf8(bytes).ok()
+    }
+
+    /// Reads an integer, which can be a number or a string of a number.
+    fn num(&mut self, signed: bool) -> Option<Num> {
+        let text = if self.peek()? == b'"' {
+            self.str()?
+        }
+        else {
+            let start = self.pos;
+            while self.pos < self.text.len() && matches!(self.text[self.pos], b'-' | b'0'..=b'9') {
+                self.pos = self.pos + 1;
+            }
+            String::from(core::str::from_utf8(&self.text[start..self.pos]).ok()?)
+        };
+        if signed {
+            text.parse::<i128>().ok().map(Num::Signed)
+        }
+        else {
+            text.parse::<u128>().ok().map(Num::Unsigned)
+        }
+    }
+
+    /// Skips a value of any type, arrays and objects nested deeper than `MAX_JSON_DEPTH` are rejected.
+    fn skip(&mut self) -> Option<()> {
+        match self.peek()? {
+            b'"' => self.str().map(|_| ()),
+            b'[' | b'{' if self.depth >= MAX_JSON_DEPTH => None,
+            b'[' => {
+                self.pos = self.pos + 1;
+                self.depth = self.depth + 1;
+                let skipped = self.list(b']', |reader| reader.skip());
+                self.depth = self.depth - 1;
+                skipped
+            },
+            b'{' => {
+                self.pos = self.pos + 1;
+                self.depth = self.depth + 1;
+                let skipped = self.list(b'}', |reader| {
+                    reader.str()?;
+                    reader.expect(b':')?;
+                    reader.skip()
+                });
+                self.depth = self.depth - 1;
+                skipped
+            },
+            _ => {
+                let start = self.pos;
+                while self.pos < self.text.len() && !matches!(self.text[self.pos], b',' | b']' | b'}' | b' ' | b'\t' | b'\n' | b'\r') {
+                    self.pos = self.pos + 1;
+                }
+                if self.pos == start {
+                    return None;
+                }
+                Some(())
+            },
+        }
+    }
+
+    fn field(&mut self, ty: NearType) -> Option<Field> {
+        let (width, signed, array, string) = shape_of(ty);
+        let field = match (array, string) {
+            (false, true) => Field::Str(self.str()?),
+            (false, false) => Field::Num(self.num(signed)?, width),
+            (true, true) => {
+                let mut texts = Vec::new();
+                self.expect(b'[')?;
+                self.list(b']', |reader| {
+                    texts.push(reader.str()?);
+                    Some(())
+                })?;
+                Field::Strs(texts)
+            },
+            (true, false) => {
+                let mut nums = Vec::new();
+                self.expect(b'[')?;
+                self.list(b']', |reader| {
+                    nums.push(reader.num(signed)?);
+                    Some(())
+                })?;
+                Field::Nums(nums, width)
+            },
+        };
+
+        Some(field)
+    }
+}
+
+/// Decodes the members `fields` of the JSON object `text`, e.g. the data returned by a Near contract.
+/// Other members are ignored. Returns `None` if `text` is not a JSON object, or a member is missing or invalid.
+pub fn json_decode(fields: &[(&str, NearType)], text: &[u8]) -> Option<Vec<MsgDetail>> {
+    let mut reader = JsonReader { text, pos: 0, depth: 0 };
+    let mut found: Vec<Option<MsgDetail>> = fields.iter().map(|_| None).collect();
+    reader.expect(b'{')?;
+    reader.list(b'}', |reader| {
+        let name = reader.str()?;
+        reader.expect(b':')?;
+        match fields.iter().position(|(field, _)| *field == name.as_str()) {
+            Some(index) => {
+                let ty = fields[index].1;
+                found[index] = Some(detail_of(ty, reader.field(ty)?)?);
+                Some(())
+            },
+            None => reader.skip(),
+        }
+    })?;
+    if reader.peek().is_some() {
+        return None;
+    }
+
+    found.into_iter().collect()
+}
+
+/// Encodes `fields` as a `MessagePayload`.
+fn scale_encode(fields: &[(String, MsgDetail)]) -> Vec<u8> {
+    let mut msg_payload = MessagePayload::new();
+    for (name, value) in fields {
+        msg_payload.push_item(name.clone(), value.clone());
+    }
+
+    msg_payload.to_bytes()
+}
+
+/// Decodes `data` as a `MessagePayload`, and returns its items named in `fields` as the expected types.
+fn scale_decode(fields: &[(&str, NearType)], data: &[u8]) -> Option<Vec<MsgDetail>> {
+    let msg_payload = MessagePayload::decode(&mut &data[..]).ok()?;
+    fields.iter().map(|(name, ty)| {
+        let item = msg_payload.get_item(String::from(*name))?;
+        let detail = match ty {
+            NearType::String => MsgDetail::InkString(item.in_to::<String>()?),
+            NearType::U8 => MsgDetail::InkU8(item.in_to::<u8>()?),
+            NearType::U16 => MsgDetail::InkU16(item.in_to::<u16>()?),
+            NearType::U32 => MsgDetail::InkU32(item.in_to::<u32>()?),
+            NearType::U64 => MsgDetail::InkU64(item.in_to::<u64>()?),
+            NearType::U128 => MsgDetail::InkU128(item.in_to::<u128>()?),
+            NearType::I8 => MsgDetail::InkI8(item.in_to::<i8>()?),
+            NearType::I16 => MsgDetail::InkI16(item.in_to::<i16>()?),
+            NearType::I32 => MsgDetail::InkI32(item.in_to::<i32>()?),
+            NearType::I64 => MsgDetail::InkI64(item.in_to::<i64>()?),
+            NearType::I128 => MsgDetail::InkI128(item.in_to::<i128>()?),
+            NearType::StringArray => MsgDetail::InkStringArray(item.in_to::<Vec<String>>()?),
+            NearType::U8Array => MsgDetail::InkU8Array(item.in_to::<Vec<u8>>()?),
+            NearType::U16Array => MsgDetail::InkU16Array(item.in_to::<Vec<u16>>()?),
+            NearType::U32Array => MsgDetail::InkU32Array(item.in_to::<Vec<u32>>()?),
+            NearType::U64Array => MsgDetail::InkU64Array(item.in_to::<Vec<u64>>()?),
+            NearType::U128Array => MsgDetail::InkU128Array(item.in_to::<Vec<u128>>()?),
+            NearType::I8Array => MsgDetail::InkI8Array(item.in_to::<Vec<i8>>()?),
+            NearType::I16Array => MsgDetail::InkI16Array(item.in_to::<Vec<i16>>()?),
+            NearType::I32Array => MsgDetail::InkI32Array(item.in_to::<Vec<i32>>()?),
+            NearType::I64Array => MsgDetail::InkI64Array(item.in_to::<Vec<i64>>()?),
+            NearType::I128Array => MsgDetail::InkI128Array(item.in_to::<Vec<i128>>()?),
+        };
+        Some(detail)
+    }).collect()
+}
+
+/// Encodes `fields` with `encoding`, Borsh encodes the values only, in order,
+/// and the SCALE encoding is a `MessagePayload` of the fields.
+pub fn encode(encoding: PayloadEncoding, fields: &[(String, MsgDetail)]) -> Result<Vec<u8>, Error> {
+    let data = match encoding {
+        PayloadEncoding::Scale => Some(scale_encode(fields)),
+        PayloadEncoding::Borsh => {
+            let values: Vec<MsgDetail> = fields.iter().map(|(_, value)| value.clone()).collect();
+            borsh_encode(&values)
+        },
+        PayloadEncoding::Json => json_encode(fields).map(|text| text.into_bytes()),
+    };
+
+    data.ok_or(Error::UnsupportedEncoding)
+}
+
+/// Decodes `data` of `encoding` as `fields`, e.g. the data of a callback from Near.
+/// Returns `Error::InvalidEncoding` if `data` can not be decoded.
+pub fn decode(encoding: PayloadEncoding, fields: &[(&str, NearType)], data: &[u8]) -> Result<Vec<MsgDetail>, Error> {
+    let values = match encoding {
+        PayloadEncoding::Scale => scale_decode(fields, data),
+        PayloadEncoding::Borsh => {
+            let types: Vec<NearType> = fields.iter().map(|(_, ty)| *ty).collect();
+            borsh_decode(&types, data)
+        },
+        PayloadEncoding::Json => json_decode(fields, data),
+    };
+
+    values.ok_or(Error::InvalidEncoding)
+}
+
+/// The codec of the named `fields` in one of the `PayloadEncoding`s, which can be returned by `PayloadCodecs::get_payload_codec`.
+/// Values are encoded and decoded in the order of `fields`.
+pub struct NearCodec {
+    /// The encoding of the data.
+    pub encoding: PayloadEncoding,
+    /// Names and types of the fields, in order.
+    pub fields: Vec<(String, NearType)>,
+}
+
+impl PayloadCodec<Vec<MsgDetail>> for NearCodec {
+    fn encode(& self, value: &Vec<MsgDetail>) -> Result<Vec<u8>, Error> {
+        if value.len() != self.fields.len() {
+            return Err(Error::UnsupportedEncoding);
+        }
+        let fields: Vec<(String, MsgDetail)> = self.fields.iter().map(|(name, _)| name.clone()).zip(value.iter().cloned()).collect();
+
+        encode(self.encoding, &fields)
+    }
+
+    fn decode(& self, data: &[u8]) -> Result<Vec<MsgDetail>, Error> {
+        let fields: Vec<(&str, NearType)> = self.fields.iter().map(|(name, ty)| (name.as_str(), *ty)).collect();
+
+        decode(self.encoding, &fields, data)
+    }
+}
+
+/// Returns the codec of `fields` in the encoding registered for the destination contract of `action` on `chain_name`.
+/// It can be returned by `PayloadCodecs::get_payload_codec`, so the codec follows the registered encoding.
+pub fn dest_codec<T: DestEncodings>(contract: &T, chain_name: &str, action: &str, fields: Vec<(String, NearType)>) -> NearCodec {
+    NearCodec {
+        encoding: contract.get_dest_encoding(String::from(chain_name), String::from(action)),
+        fields,
+    }
+}
+
+/// Sends `fields` to the destination contract registered for `action` on `chain_name`, and returns the message id.
+/// The data is encoded with the encoding registered for the destination, which is `MessagePayload` for `PayloadEncoding::Scale`.
+pub fn cross_chain_send_fields<T: CrossChainBase + MultiDestContracts + DestEncodings>(contract: &mut T, chain_name: String, action: String, fields: &[(String, MsgDetail)]) -> Result<u128, Error> {
+    let (dest_contract, dest_action) = contract.get_dest_contract_info(chain_name.clone(), action.clone()).ok_or(Error::DestNotRegistered)?;
+    cross_chain_helper::check_payload_items(contract, &chain_name, fields)?;
+    let data = encode(contract.get_dest_encoding(chain_name.clone(), action), fields)?;
+
+    let content = IContent::new(dest_contract, dest_action, data);
+    let request = IRequestMessage::new(chain_name, Vec::<ISQoS>::new(), content);
+    cross_chain_helper::cross_chain_send_message(contract, request)
+}
+
+/// Receives a cross-chain message like `cross_chain_receive`, and decodes its raw `data` as `fields`
+/// with the codec of the destination contract of `action` on the source chain, see `dest_codec`,
+/// e.g. the callback of a call sent with `cross_chain_send_fields` to a Near contract.
+pub fn cross_chain_receive_fields<T: CrossChainBase + DestEncodings>(contract: &mut T, action: String, fields: &[(&str, NearType)], data: &[u8]) -> Result<(IContext, Vec<MsgDetail>), Error> {
+    let context = cross_chain_helper::cross_chain_receive(contract)?;
+    let fields: Vec<(String, NearType)> = fields.iter().map(|(name, ty)| (String::from(*name), *ty)).collect();
+    let values = dest_codec(contract, &context.from_chain, &action, fields).decode(data)?;
+
+    Ok((context, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// We test if fields are encoded like the Near SDK does, and decoded back.
+    #[test]
+    fn borsh_and_json_work() {
+        let fields = [
+            (String::from("greeting"), MsgDetail::InkStringArray(ink::prelude::vec![String::from("Hi \"Near\"")])),
+            (String::from("amount"), MsgDetail::InkU128(7)),
+            (String::from("delta"), MsgDetail::InkI32(-2)),
+        ];
+        let types = [
+            ("greeting", NearType::StringArray),
+            ("amount", NearType::U128),
+            ("delta", NearType::I32),
+        ];
+
+        let data = encode(PayloadEncoding::Borsh, &fields).unwrap();
+        assert_eq!(data.len(), 4 + 4 + 9 + 16 + 4);
+        assert_eq!(&data[data.len() - 4..], &[0xfe, 0xff, 0xff, 0xff]);
+        assert_eq!(decode(PayloadEncoding::Borsh, &types, &data).unwrap().len(), 3);
+        assert_eq!(decode(PayloadEncoding::Borsh, &types, &data[..data.len() - 1]).err(), Some(Error::InvalidEncoding));
+
+        let data = encode(PayloadEncoding::Json, &fields).unwrap();
+        assert_eq!(data, br#"{"greeting":["Hi \"Near\""],"amount":"7","delta":-2}"#.to_vec());
+        let response = br#"{ "delta": -2, "extra": {"a": [1, null]}, "amount": 7, "greeting": ["Hi \"Near\""] }"#;
+        assert_eq!(decode(PayloadEncoding::Json, &types, response).unwrap().len(), 3);
+        assert_eq!(decode(PayloadEncoding::Json, &types[..1], br#"{"amount": "7"}"#).err(), Some(Error::InvalidEncoding));
+        let nested = |depth: usize| {
+            let mut data = Vec::from(&br#"{"amount": 7, "extra": "#[..]);
+            data.extend(core::iter::repeat(b'[').take(depth));
+            data.extend(core::iter::repeat(b']').take(depth));
+            data.push(b'}');
+            data
+        };
+        assert_eq!(decode(PayloadEncoding::Json, &types[1..2], &nested(MAX_JSON_DEPTH)).unwrap().len(), 1);
+        assert_eq!(decode(PayloadEncoding::Json, &types[1..2], &nested(MAX_JSON_DEPTH + 1)).err(), Some(Error::InvalidEncoding));
+        assert_eq!(decode(PayloadEncoding::Json, &types[1..2], &nested(100_000)).err(), Some(Error::InvalidEncoding));
+
+        let data = encode(PayloadEncoding::Scale, &fields).unwrap();
+        assert_eq!(decode(PayloadEncoding::Scale, &types, &data).unwrap().len(), 3);
+        assert_eq!(decode(PayloadEncoding::Scale, &[("missing", NearType::U8)], &data).err(), Some(Error::InvalidEncoding));
+    }
+}
@@ -0,0 +1,168 @@
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use ink::storage::Mapping;
+use payload::message_define::{
+    ISQoS,
+    IContent,
+    IContext,
+    IRequestMessage,
+};
+use payload::message_protocol::{
+    MsgDetail,
+    MessagePayload,
+};
+use crate::cross_chain_helper::{
+    self,
+    CrossChainBase,
+    Error,
+};
+use crate::chain_registry;
+
+/// Name of the payload item which carries the sequence number of an ordered message.
+pub const SEQUENCE_ITEM: &str = "__sequence";
+
+/// Default number of sequence numbers ahead of the expected one which can be buffered per sender.
+const DEFAULT_WINDOW: u128 = 64;
+
+/// Ordered delivery channels per remote contract.
+///
+/// Messages sent through the channel carry sequence numbers starting from 0 per destination chain and contract.
+/// Messages received are ordered per source chain and sender, those received out of order are buffered with their context,
+/// and released in sequence once the missing ones arrive.
+#[ink::storage_item]
+#[derive(Default)]
+pub struct OrderedChannel {
+    window: u128,
+    next_send: Mapping<(String, Vec<u8>), u128>,
+    next_receive: Mapping<(String, Vec<u8>), u128>,
+    buffer: Mapping<(String, Vec<u8>, u128), (IContext, MessagePayload)>,
+}
+
+impl OrderedChannel {
+    /// Returns how many sequence numbers ahead of the expected one can be buffered.
+    pub fn window(& self) -> u128 {
+        if self.window == 0 {
+            DEFAULT_WINDOW
+        }
+        else {
+            self.window
+        }
+    }
+
+    /// Sets how many sequence numbers ahead of the expected one can be buffered, 0 means the default.
+    pub fn set_window(&mut self, window: u128) {
+        self.window = window;
+    }
+
+    /// Returns the sequence number of the next message sent to the contract `dest_contract` on `chain_name`.
+    pub fn next_send_sequence(& self, chain_name: &str, dest_contract: &[u8]) -> u128 {
+        self.next_send.get((chain_name, dest_contract)).unwrap_or(0)
+    }
+
+    /// Returns the sequence number of the next message expected from `sender` on `chain_name`.
+    pub fn next_receive_sequence(& self, chain_name: &str, sender: &[u8]) -> u128 {
+        self.next_receive.get((chain_name, sender)).unwrap_or(0)
+    }
+
+    /// Receives an ordered message with its `context`, and returns the messages from the same sender which can be handled now,
+    /// in sequence, with their contexts. If the message arrives before the ones it follows, it is buffered, and nothing is returned.
+    /// The sequence item is stripped from the payloads returned, so handlers only see the items of the application.
+    pub fn receive(&mut self, context: IContext, mut payload: MessagePayload) -> Result<Vec<(IContext, MessagePayload)>, Error> {
+        let sequence = payload.get_item(String::from(SEQUENCE_ITEM))
+            .and_then(|item| item.in_to::<u128>())
+            .ok_or(Error::MissingSequence)?;
+        payload.items.retain(|item| item.n != SEQUENCE_ITEM);
+        let chain_name = context.from_chain.clone();
+        let sender = context.sender.clone();
+
+        let mut next = self.next_receive_sequence(&chain_name, &sender);
+        if sequence < next || self.buffer.contains((&chain_name, &sender, sequence)) {
+            return Err(Error::DuplicateMessage);
+        }
+        if sequence - next >= self.window() {
+            return Err(Error::SequenceOutOfWindow);
+        }
+        if sequence > next {
+            self.buffer.insert((&chain_name, &sender, sequence), &(context, payload));
+            return Ok(Vec::new());
+        }
+
+        let mut ready = Vec::new();
+        ready.push((context, payload));
+        next = next + 1;
+        while let Some(buffered) = self.buffer.get((&chain_name, &sender, next)) {
+            self.buffer.remove((&chain_name, &sender, next));
+            ready.push(buffered);
+            next = next + 1;
+        }
+        self.next_receive.insert((&chain_name, &sender), &next);
+
+        Ok(ready)
+    }
+}
+
+/// If you want to use ordered channels, this trait must be implemented.
+pub trait OrderedChannels: CrossChainBase {
+    /// Returns the ordered channels of the contract.
+    fn get_ordered_channel_mut(&mut self) -> &mut OrderedChannel;
+}
+
+/// Sends an ordered message to the destination contract and action `dest` on `to_chain`, and returns the message id.
+/// The sequence number of the destination contract is attached to `payload`, and only consumed if the message is sent.
+pub fn cross_chain_send_ordered<T: OrderedChannels>(contract: &mut T, to_chain: String, sqos: Vec<ISQoS>, dest: (Vec<u8>, Vec<u8>), mut payload: MessagePayload) -> Result<u128, Error> {
+    let chain_name = chain_registry::canonical_chain_name(contract, &to_chain)?;
+    let sequence = contract.get_ordered_channel_mut().next_send_sequence(&chain_name, &dest.0);
+    payload.push_item(String::from(SEQUENCE_ITEM), MsgDetail::InkU128(sequence));
+
+    let key = (chain_name.clone(), dest.0.clone());
+    let content = IContent::new(dest.0, dest.1, payload.to_bytes());
+    let request = IRequestMessage::new(chain_name, sqos, content);
+    let id = cross_chain_helper::cross_chain_send_message(contract, request)?;
+
+    contract.get_ordered_channel_mut().next_send.insert(&key, &(sequence + 1));
+
+    Ok(id)
+}
+
+/// Receives an ordered message, and returns the messages from its sender which can be handled now, in sequence, with their contexts.
+/// It should be called instead of `cross_chain_receive` by messages which receive ordered messages.
+pub fn cross_chain_receive_ordered<T: OrderedChannels>(contract: &mut T, payload: MessagePayload) -> Result<Vec<(IContext, MessagePayload)>, Error> {
+    let context = cross_chain_helper::cross_chain_receive(contract)?;
+
+    contract.get_ordered_channel_mut().receive(context, payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use payload::message_define::ISession;
+
+    fn ordered_message(sender: u8, sequence: u128) -> (IContext, MessagePayload) {
+        let session = ISession::new(0, 1, Vec::<u8>::new(), Vec::<u8>::new(), Vec::<u8>::new());
+        let context = IContext::new(sequence + 1, String::from("ETHEREUM"), ink::prelude::vec![sender; 20], Vec::<u8>::new(), Vec::<ISQoS>::new(), [0; 32], [0; 4], session);
+        let mut payload = MessagePayload::new();
+        payload.push_item(String::from(SEQUENCE_ITEM), MsgDetail::InkU128(sequence));
+        (context, payload)
+    }
+
+    /// We test if messages received out of order are released in sequence per sender, with their contexts.
+    #[ink::test]
+    fn receive_releases_in_sequence() {
+        let mut channel = OrderedChannel::default();
+        let receive = |channel: &mut OrderedChannel, sender: u8, sequence: u128| {
+            let (context, payload) = ordered_message(sender, sequence);
+            channel.receive(context, payload)
+        };
+        assert_eq!(receive(&mut channel, 1, 2).unwrap().len(), 0);
+        assert_eq!(receive(&mut channel, 1, 1).unwrap().len(), 0);
+        assert_eq!(receive(&mut channel, 1, 1).err(), Some(Error::DuplicateMessage));
+        assert_eq!(receive(&mut channel, 2, 0).unwrap().len(), 1);
+
+        let ready = receive(&mut channel, 1, 0).unwrap();
+        assert_eq!(ready.iter().map(|(context, _)| context.id).collect::<Vec<u128>>(), [1, 2, 3]);
+        assert!(ready.iter().all(|(_, payload)| payload.get_item(String::from(SEQUENCE_ITEM)).is_none()));
+        assert_eq!(channel.next_receive_sequence("ETHEREUM", &[1; 20]), 3);
+        assert_eq!(channel.next_receive_sequence("ETHEREUM", &[2; 20]), 1);
+        assert_eq!(receive(&mut channel, 1, 3 + DEFAULT_WINDOW).err(), Some(Error::SequenceOutOfWindow));
+    }
+}
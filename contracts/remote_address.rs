@@ -0,0 +1,183 @@
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use crate::cross_chain_helper::{
+    CrossChainBase,
+    Error,
+};
+
+/// Families of chains, which share the address format.
+#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ChainFamily {
+    /// Ethereum and other EVM chains, e.g. Avalanche.
+    Evm,
+    /// Near.
+    Near,
+    /// Polkadot, Kusama and their parachains.
+    Substrate,
+    /// Flow.
+    Flow,
+}
+
+/// Address of a contract or an account on another chain.
+#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum RemoteAddress {
+    /// 20-byte EVM address.
+    Evm([u8; 20]),
+    /// Near account id, e.g. `greeting.testnet`.
+    Near(String),
+    /// 32-byte Substrate account id.
+    Substrate([u8; 32]),
+    /// 8-byte Flow address.
+    Flow([u8; 8]),
+}
+
+impl RemoteAddress {
+    /// Decodes the raw bytes of an address of `family`, as they are carried in messages.
+    /// Returns `None` if the length or the encoding does not match the family.
+    pub fn from_bytes(family: ChainFamily, bytes: &[u8]) -> Option<Self> {
+        match family {
+            ChainFamily::Evm => bytes.try_into().ok().map(RemoteAddress::Evm),
+            ChainFamily::Near => String::from_utf8(bytes.to_vec()).ok()
+                .filter(|account_id| is_near_account_id(account_id))
+                .map(RemoteAddress::Near),
+            ChainFamily::Substrate => bytes.try_into().ok().map(RemoteAddress::Substrate),
+            ChainFamily::Flow => bytes.try_into().ok().map(RemoteAddress::Flow),
+        }
+    }
+
+    /// Parses the text form of an address of `family`.
+    /// EVM, Substrate and Flow addresses are hex strings with an optional `0x` prefix, Near addresses are account ids.
+    /// Returns `None` if the text is not a valid address of the family.
+    pub fn parse(family: ChainFamily, text: &str) -> Option<Self> {
+        match family {
+            ChainFamily::Evm => decode_hex(text).map(RemoteAddress::Evm),
+            ChainFamily::Near => Self::from_bytes(family, text.as_bytes()),
+            ChainFamily::Substrate => decode_hex(text).map(RemoteAddress::Substrate),
+            ChainFamily::Flow => decode_hex(text).map(RemoteAddress::Flow),
+        }
+    }
+
+    /// Returns the text form of the address, which can be parsed by `parse`.
+    /// EVM, Substrate and Flow addresses are formatted as lowercase hex strings with the `0x` prefix.
+    pub fn format(& self) -> String {
+        match self {
+            RemoteAddress::Evm(address) => encode_hex(address),
+            RemoteAddress::Near(account_id) => account_id.clone(),
+            RemoteAddress::Substrate(address) => encode_hex(address),
+            RemoteAddress::Flow(address) => encode_hex(address),
+        }
+    }
+
+    /// Returns the raw bytes of the address, as they are carried in messages.
+    pub fn to_bytes(& self) -> Vec<u8> {
+        match self {
+            RemoteAddress::Evm(address) => address.to_vec(),
+            RemoteAddress::Near(account_id) => account_id.as_bytes().to_vec(),
+            RemoteAddress::Substrate(address) => address.to_vec(),
+            RemoteAddress::Flow(address) => address.to_vec(),
+        }
+    }
+
+    /// Returns the chain family of the address.
+    pub fn family(& self) -> ChainFamily {
+        match self {
+            RemoteAddress::Evm(_) => ChainFamily::Evm,
+            RemoteAddress::Near(_) => ChainFamily::Near,
+            RemoteAddress::Substrate(_) => ChainFamily::Substrate,
+            RemoteAddress::Flow(_) => ChainFamily::Flow,
+        }
+    }
+}
+
+/// Returns if `account_id` is a valid Near account id.
+/// It has 2 to 64 characters, which are lowercase letters, digits, or separators `-`, `_` and `.`,
+/// and separators can not be at either end, or next to each other.
+fn is_near_account_id(account_id: &str) -> bool {
+    let bytes = account_id.as_bytes();
+    if bytes.len() < 2 || bytes.len() > 64 {
+        return false;
+    }
+
+    let is_separator = |c: u8| c == b'-' || c == b'_' || c == b'.';
+    let mut last_is_separator = true;
+    for &c in bytes {
+        if is_separator(c) {
+            if last_is_separator {
+                return false;
+            }
+            last_is_separator = true;
+        }
+        else if c.is_ascii_lowercase() || c.is_ascii_digit() {
+            last_is_separator = false;
+        }
+        else {
+            return false;
+        }
+    }
+
+    !last_is_separator
+}
+
+/// Decodes a hex string of exactly `N` bytes, with an optional `0x` prefix.
+fn decode_hex<const N: usize>(text: &str) -> Option<[u8; N]> {
+    let hex = text.strip_prefix("0x").unwrap_or(text).as_bytes();
+    if hex.len() != N * 2 {
+        return None;
+    }
+
+    let mut bytes = [0u8; N];
+    for (byte, pair) in bytes.iter_mut().zip(hex.chunks(2)) {
+        let high = (pair[0] as char).to_digit(16)?;
+        let low = (pair[1] as char).to_digit(16)?;
+        *byte = (high << 4 | low) as u8;
+    }
+
+    Some(bytes)
+}
+
+/// Encodes `bytes` as a lowercase hex string with the `0x` prefix.
+fn encode_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut text = String::from("0x");
+    for byte in bytes {
+        text.push(DIGITS[(byte >> 4) as usize] as char);
+        text.push(DIGITS[(byte & 0x0f) as usize] as char);
+    }
+
+    text
+}
+
+/// Validates the raw address `dest_contract` of a destination contract on `chain_name`.
+/// It should be called by `MultiDestContracts::register_dest_contract`.
+/// Returns `Error::InvalidAddress` if the chain family of `chain_name` is known, and the address does not match it.
+pub fn validate_dest_contract<T: CrossChainBase>(contract: &T, chain_name: &str, dest_contract: &[u8]) -> Result<(), Error> {
+    match contract.get_chain_family(chain_name) {
+        Some(family) => RemoteAddress::from_bytes(family, dest_contract).map(|_| ()).ok_or(Error::InvalidAddress),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// We test if addresses are parsed and formatted per chain family, and invalid ones are rejected.
+    #[test]
+    fn parse_and_format_work() {
+        let evm = RemoteAddress::parse(ChainFamily::Evm, "0x5FbDB2315678afecb367f032d93F642f64180aa3").unwrap();
+        assert_eq!(evm.to_bytes().len(), 20);
+        assert_eq!(evm.format(), "0x5fbdb2315678afecb367f032d93f642f64180aa3");
+        assert_eq!(RemoteAddress::from_bytes(ChainFamily::Evm, b"5FbDB2315678afecb367f032d93F642f64180aa3"), None);
+
+        assert_eq!(RemoteAddress::parse(ChainFamily::Flow, "f8d6e0586b0a20c7"), Some(RemoteAddress::Flow([0xf8, 0xd6, 0xe0, 0x58, 0x6b, 0x0a, 0x20, 0xc7])));
+        assert_eq!(RemoteAddress::parse(ChainFamily::Substrate, "0x01"), None);
+        assert_eq!(RemoteAddress::parse(ChainFamily::Flow, "+8d6e0586b0a20c7"), None);
+        assert_eq!(RemoteAddress::parse(ChainFamily::Flow, "f8d6e0586b0a20\u{e9}"), None);
+
+        assert!(RemoteAddress::parse(ChainFamily::Near, "greeting.dante.testnet").is_some());
+        assert_eq!(RemoteAddress::parse(ChainFamily::Near, "Greeting.testnet"), None);
+        assert_eq!(RemoteAddress::parse(ChainFamily::Near, "greeting..testnet"), None);
+    }
+}
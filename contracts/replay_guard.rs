@@ -0,0 +1,115 @@
+use ink::primitives::AccountId;
+use ink::prelude::string::String;
+use ink::storage::Mapping;
+use crate::cross_chain_helper::Error;
+
+/// Number of message ids recorded in one bitmap word.
+const WORD_BITS: u128 = 128;
+
+/// Records ids of processed messages per delivering cross-chain contract and source chain, to reject replayed or duplicated messages.
+/// Each cross-chain contract numbers its messages on its own, so the ids delivered by different ones are recorded apart.
+///
+/// Message ids of a source chain start from 1. All ids not greater than the high-water mark of the chain
+/// have been processed, and ids processed out of order are recorded in bitmaps of 128 ids above the mark.
+/// A bitmap is removed as soon as the mark passes it, so the storage keeps compact when messages arrive in order.
+#[ink::storage_item]
+#[derive(Default)]
+pub struct ReplayGuard {
+    marks: Mapping<(AccountId, String), u128>,
+    bitmaps: Mapping<(AccountId, String, u128), u128>,
+}
+
+impl ReplayGuard {
+    /// Returns the high-water mark of `chain_name` delivered by `cross_chain`, all ids not greater than it have been processed.
+    pub fn high_water_mark(& self, cross_chain: &AccountId, chain_name: &str) -> u128 {
+        self.marks.get((cross_chain, chain_name)).unwrap_or(0)
+    }
+
+    /// Returns if the message `id` from `chain_name` delivered by `cross_chain` has been processed.
+    pub fn is_processed(& self, cross_chain: &AccountId, chain_name: &str, id: u128) -> bool {
+        if id <= self.high_water_mark(cross_chain, chain_name) {
+            return true;
+        }
+
+        let bitmap = self.bitmaps.get((cross_chain, chain_name, id / WORD_BITS)).unwrap_or(0);
+        bitmap & (1u128 << (id % WORD_BITS)) != 0
+    }
+
+    /// Records the message `id` from `chain_name` delivered by `cross_chain` as processed.
+    /// Returns `Error::DuplicateMessage` if it has been processed before.
+    pub fn mark_processed(&mut self, cross_chain: &AccountId, chain_name: &str, id: u128) -> Result<(), Error> {
+        if self.is_processed(cross_chain, chain_name, id) {
+            return Err(Error::DuplicateMessage);
+        }
+
+        if id != self.high_water_mark(cross_chain, chain_name) + 1 {
+            let word = id / WORD_BITS;
+            let bitmap = self.bitmaps.get((cross_chain, chain_name, word)).unwrap_or(0);
+            self.bitmaps.insert((cross_chain, chain_name, word), &(bitmap | (1u128 << (id % WORD_BITS))));
+            return Ok(());
+        }
+
+        // Advances the mark over the ids which have been processed out of order.
+        let mut mark = id;
+        loop {
+            let word = (mark + 1) / WORD_BITS;
+            let bit = (mark + 1) % WORD_BITS;
+            let bitmap = self.bitmaps.get((cross_chain, chain_name, word)).unwrap_or(0);
+            let run = (bitmap >> bit).trailing_ones() as u128;
+            mark = mark + run;
+
+            if bit + run < WORD_BITS {
+                if run > 0 {
+                    let rest = bitmap & !((1u128 << (bit + run)) - 1);
+                    if rest == 0 {
+                        self.bitmaps.remove((cross_chain, chain_name, word));
+                    }
+                    else {
+                        self.bitmaps.insert((cross_chain, chain_name, word), &rest);
+                    }
+                }
+                break;
+            }
+
+            self.bitmaps.remove((cross_chain, chain_name, word));
+        }
+
+        self.marks.insert((cross_chain, chain_name), &mark);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// We test if duplicated messages are rejected, per delivering cross-chain contract and source chain.
+    #[ink::test]
+    fn mark_processed_rejects_duplicates() {
+        let mut guard = ReplayGuard::default();
+        let cross_chain = AccountId::from([0x01; 32]);
+        assert_eq!(guard.mark_processed(&cross_chain, "ETHEREUM", 1), Ok(()));
+        assert_eq!(guard.mark_processed(&cross_chain, "ETHEREUM", 1), Err(Error::DuplicateMessage));
+        assert_eq!(guard.mark_processed(&cross_chain, "NEAR", 1), Ok(()));
+        assert_eq!(guard.mark_processed(&AccountId::from([0x02; 32]), "ETHEREUM", 1), Ok(()));
+    }
+
+    /// We test if the high-water mark advances over messages processed out of order.
+    #[ink::test]
+    fn mark_processed_advances_mark() {
+        let mut guard = ReplayGuard::default();
+        let cross_chain = AccountId::from([0x01; 32]);
+        for id in (2..300).rev() {
+            assert_eq!(guard.mark_processed(&cross_chain, "ETHEREUM", id), Ok(()));
+        }
+        assert_eq!(guard.high_water_mark(&cross_chain, "ETHEREUM"), 0);
+        assert!(guard.is_processed(&cross_chain, "ETHEREUM", 130));
+        assert!(!guard.is_processed(&cross_chain, "ETHEREUM", 1));
+
+        assert_eq!(guard.mark_processed(&cross_chain, "ETHEREUM", 1), Ok(()));
+        assert_eq!(guard.high_water_mark(&cross_chain, "ETHEREUM"), 299);
+        assert_eq!(guard.mark_processed(&cross_chain, "ETHEREUM", 130), Err(Error::DuplicateMessage));
+        assert!(!guard.is_processed(&cross_chain, "ETHEREUM", 300));
+    }
+}
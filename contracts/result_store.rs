@@ -0,0 +1,104 @@
+use ink::prelude::string::String;
+use ink::storage::Mapping;
+use ink::storage::traits::Packed;
+use crate::cross_chain_helper::Error;
+
+/// Typed results of cross-chain messages, keyed by chain name and message id.
+///
+/// Results older than `max_age` blocks are treated as missing, and can be removed by anyone with `remove_expired`.
+/// Results whose encoded size exceeds `max_size` are rejected, to protect storage deposits.
+#[ink::storage_item]
+pub struct ResultStore<T: Packed> {
+    max_age: Option<u32>,
+    max_size: Option<u32>,
+    values: Mapping<(String, u128), T>,
+    stored_at: Mapping<(String, u128), u32>,
+}
+
+impl<T: Packed> Default for ResultStore<T> {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+impl<T: Packed> ResultStore<T> {
+    /// Creates the store, results expire after `max_age` blocks, and can not exceed `max_size` bytes when encoded.
+    pub fn new(max_age: Option<u32>, max_size: Option<u32>) -> Self {
+        Self {
+            max_age,
+            max_size,
+            values: Default::default(),
+            stored_at: Default::default(),
+        }
+    }
+
+    /// Returns if the result stored at block `stored_at` has expired.
+    fn is_expired(& self, stored_at: u32) -> bool {
+        match self.max_age {
+            Some(max_age) => ink::env::block_number::<ink::env::DefaultEnvironment>().saturating_sub(stored_at) > max_age,
+            None => false,
+        }
+    }
+
+    /// Stores `value` as the result of the message `id` of `chain_name`, replacing the previous one.
+    pub fn insert(&mut self, chain_name: &str, id: u128, value: &T) -> Result<(), Error> {
+        if let Some(max_size) = self.max_size {
+            if value.encoded_size() > max_size as usize {
+                return Err(Error::ResultTooLarge);
+            }
+        }
+
+        self.values.insert((chain_name, id), value);
+        self.stored_at.insert((chain_name, id), &ink::env::block_number::<ink::env::DefaultEnvironment>());
+
+        Ok(())
+    }
+
+    /// Returns the result of the message `id` of `chain_name`, if it exists and has not expired.
+    pub fn get(& self, chain_name: &str, id: u128) -> Option<T> {
+        let stored_at = self.stored_at.get((chain_name, id))?;
+        if self.is_expired(stored_at) {
+            return None;
+        }
+
+        self.values.get((chain_name, id))
+    }
+
+    /// Removes the result of the message `id` of `chain_name`.
+    pub fn remove(&mut self, chain_name: &str, id: u128) {
+        self.values.remove((chain_name, id));
+        self.stored_at.remove((chain_name, id));
+    }
+
+    /// Removes the result of the message `id` of `chain_name` if it has expired, returns if it is removed.
+    pub fn remove_expired(&mut self, chain_name: &str, id: u128) -> bool {
+        match self.stored_at.get((chain_name, id)) {
+            Some(stored_at) if self.is_expired(stored_at) => {
+                self.remove(chain_name, id);
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// We test if results expire, and the size cap works.
+    #[ink::test]
+    fn result_store_works() {
+        let mut store = ResultStore::<String>::new(Some(1), Some(8));
+        assert_eq!(store.insert("ETHEREUM", 1, &String::from("Hello")), Ok(()));
+        assert_eq!(store.insert("ETHEREUM", 2, &String::from("Hello, Polkadot")), Err(Error::ResultTooLarge));
+        assert_eq!(store.get("ETHEREUM", 1), Some(String::from("Hello")));
+        assert_eq!(store.get("ETHEREUM", 2), None);
+
+        ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+        assert!(!store.remove_expired("ETHEREUM", 1));
+        ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+        assert_eq!(store.get("ETHEREUM", 1), None);
+        assert!(store.remove_expired("ETHEREUM", 1));
+    }
+}
@@ -0,0 +1,199 @@
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use ink::storage::Mapping;
+use payload::message_define::ISentMessage;
+
+/// How many times a message can be sent at most, if the store is created with `Default`.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// Backoff in blocks of the first retry, if the store is created with `Default`.
+pub const DEFAULT_BACKOFF: u32 = 10;
+
+/// A copy of an outbound message kept for retrying.
+#[derive(scale::Encode, scale::Decode, Debug, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct KeptMessage {
+    /// Destination chain.
+    pub chain: String,
+    /// Destination action.
+    pub action: Vec<u8>,
+    /// Session type of the message.
+    pub session_type: u8,
+    /// The message sent to the cross-chain contract.
+    pub message: ISentMessage,
+    /// How many times the message has been sent.
+    pub attempts: u32,
+    /// Block number when the message was sent last time.
+    pub last_sent: u32,
+    /// Ids of the retries of the message, in order.
+    pub retries: Vec<u128>,
+}
+
+/// Copies of outbound messages keyed by destination chain and the id they were first sent with,
+/// which can be sent again with `retry_message`.
+/// Expose it with `CrossChainBase::get_retry_store_mut`, and the helpers will keep a copy of every message they send,
+/// and remove it once a response or an error of any attempt arrives.
+#[ink::storage_item]
+pub struct RetryStore {
+    max_attempts: u32,
+    backoff: u32,
+    messages: Mapping<(String, u128), KeptMessage>,
+    originals: Mapping<(String, u128), u128>,
+}
+
+impl Default for RetryStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ATTEMPTS, DEFAULT_BACKOFF)
+    }
+}
+
+impl RetryStore {
+    /// Creates the store, a message can be sent at most `max_attempts` times,
+    /// and the `n`th retry is allowed `backoff * 2^(n - 1)` blocks after the last attempt.
+    pub fn new(max_attempts: u32, backoff: u32) -> Self {
+        Self {
+            max_attempts,
+            backoff,
+            messages: Default::default(),
+            originals: Default::default(),
+        }
+    }
+
+    /// Returns how many times a message can be sent at most.
+    pub fn max_attempts(& self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Returns the id which the message `id` sent to `chain_name` was first sent with.
+    pub fn original_of(& self, chain_name: &str, id: u128) -> u128 {
+        self.originals.get((chain_name, id)).unwrap_or(id)
+    }
+
+    /// Returns the kept copy of the message `id` sent to `chain_name`, `id` can be the id of any attempt.
+    pub fn get(& self, chain_name: &str, id: u128) -> Option<KeptMessage> {
+        self.messages.get((chain_name, self.original_of(chain_name, id)))
+    }
+
+    /// Returns the block number from which `kept` can be retried.
+    pub fn next_retry_at(& self, kept: &KeptMessage) -> u32 {
+        let exponent = kept.attempts.saturating_sub(1).min(31);
+        kept.last_sent.saturating_add(self.backoff.saturating_mul(1u32 << exponent))
+    }
+
+    /// Keeps a copy of the message `original` sent to `kept.chain`, and links the ids of its retries to it.
+    pub fn keep(&mut self, original: u128, kept: &KeptMessage) {
+        self.messages.insert((&kept.chain, original), kept);
+        if let Some(id) = kept.retries.last() {
+            self.originals.insert((&kept.chain, *id), &original);
+        }
+    }
+
+    /// Removes the kept copy of the message `id` sent to `chain_name`, and the links of its retries,
+    /// e.g. once it is responded. `id` can be the id of any attempt.
+    pub fn remove(&mut self, chain_name: &str, id: u128) {
+        let original = self.original_of(chain_name, id);
+        if let Some(kept) = self.messages.get((chain_name, original)) {
+            for retry in kept.retries {
+                self.originals.remove((chain_name, retry));
+            }
+            self.messages.remove((chain_name, original));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use payload::message_define::{
+        IContent,
+        IRequestMessage,
+        ISession,
+    };
+    use crate::cross_chain_helper::{
+        self,
+        CrossChainBase,
+        Error,
+    };
+    use crate::transport::CrossChainTransport;
+    use crate::mock::MockTransport;
+
+    struct MockContract {
+        transport: MockTransport,
+        retry_store: RetryStore,
+    }
+
+    impl CrossChainBase for MockContract {
+        fn get_retry_store_mut(&mut self) -> Option<&mut RetryStore> {
+            Some(&mut self.retry_store)
+        }
+
+        fn get_transport(& self) -> &dyn CrossChainTransport {
+            &self.transport
+        }
+    }
+
+    fn kept_message(attempts: u32, last_sent: u32) -> KeptMessage {
+        let content = IContent::new(Vec::<u8>::new(), Vec::<u8>::new(), Vec::<u8>::new());
+        let session = ISession::new(0, 1, Vec::<u8>::new(), Vec::<u8>::new(), Vec::<u8>::new());
+        KeptMessage {
+            chain: String::from("ETHEREUM"),
+            action: Vec::new(),
+            session_type: 1,
+            message: ISentMessage::new(String::from("ETHEREUM"), Vec::new(), content, session),
+            attempts,
+            last_sent,
+            retries: Vec::new(),
+        }
+    }
+
+    /// We test if the backoff doubles after every attempt, and saturates instead of overflowing.
+    #[ink::test]
+    fn next_retry_at_works() {
+        let store = RetryStore::new(5, 10);
+        assert_eq!(store.next_retry_at(&kept_message(1, 100)), 110);
+        assert_eq!(store.next_retry_at(&kept_message(2, 100)), 120);
+        assert_eq!(store.next_retry_at(&kept_message(3, 100)), 140);
+        assert_eq!(store.next_retry_at(&kept_message(40, 100)), u32::MAX);
+        assert_eq!(store.next_retry_at(&kept_message(1, u32::MAX - 5)), u32::MAX);
+        assert_eq!(RetryStore::default().max_attempts(), DEFAULT_MAX_ATTEMPTS);
+    }
+
+    /// We test if retries are linked to the original message per chain, and removed together.
+    #[ink::test]
+    fn keep_and_remove_works() {
+        let mut store = RetryStore::default();
+        let mut kept = kept_message(1, 0);
+        store.keep(1, &kept);
+        kept.attempts = 2;
+        kept.retries.push(7);
+        store.keep(1, &kept);
+        assert_eq!(store.original_of("ETHEREUM", 7), 1);
+        assert_eq!(store.original_of("NEAR", 7), 7);
+        assert_eq!(store.get("ETHEREUM", 7).map(|kept| kept.retries), Some(Vec::from([7])));
+        assert!(store.get("NEAR", 1).is_none());
+
+        store.remove("ETHEREUM", 7);
+        assert!(store.get("ETHEREUM", 1).is_none());
+        assert_eq!(store.original_of("ETHEREUM", 7), 7);
+    }
+
+    /// We test if a message can be retried after the backoff, until it has been sent the max attempts.
+    #[ink::test]
+    fn retry_limits_work() {
+        let mut contract = MockContract {
+            transport: MockTransport::default(),
+            retry_store: RetryStore::new(2, 1),
+        };
+        let content = IContent::new(Vec::<u8>::new(), Vec::<u8>::new(), Vec::<u8>::new());
+        let request = IRequestMessage::new(String::from("ETHEREUM"), Vec::new(), content);
+        assert_eq!(cross_chain_helper::cross_chain_send_message(&mut contract, request), Ok(1));
+
+        assert_eq!(cross_chain_helper::retry_message(&mut contract, String::from("ETHEREUM"), 1), Err(Error::RetryTooEarly));
+        ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+        assert_eq!(cross_chain_helper::retry_message(&mut contract, String::from("ETHEREUM"), 1), Ok(2));
+        ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+        ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+        assert_eq!(cross_chain_helper::retry_message(&mut contract, String::from("ETHEREUM"), 2), Err(Error::TooManyAttempts));
+        assert_eq!(cross_chain_helper::retry_message(&mut contract, String::from("NEAR"), 1), Err(Error::MessageNotKept));
+    }
+}
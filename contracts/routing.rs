@@ -0,0 +1,191 @@
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use scale::{
+    Decode,
+    Encode,
+};
+use payload::message_define::{
+    ISQoS,
+    IContent,
+    IContext,
+    IRequestMessage,
+};
+use payload::message_protocol::{
+    MsgDetail,
+    MessagePayload,
+};
+use crate::MultiDestContracts;
+use crate::cross_chain_helper::{
+    self,
+    CrossChainBase,
+    Error,
+};
+
+/// Action name under which relay contracts on intermediate chains are registered in `MultiDestContracts`.
+pub const RELAY_ACTION: &str = "relay";
+
+/// Name of the payload item which carries the forwarding envelope.
+pub const ENVELOPE_ITEM: &str = "__envelope";
+
+/// A message forwarded through intermediate chains.
+#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct ForwardEnvelope {
+    /// Chains the message has not reached yet, the last one is the final destination.
+    pub route: Vec<String>,
+    /// Source chain and sender of the message, which are set by the first relay from its delivery context.
+    pub origin: Option<(String, Vec<u8>)>,
+    /// Destination contract on the final destination.
+    pub contract: Vec<u8>,
+    /// Destination action on the final destination.
+    pub action: Vec<u8>,
+    /// Data delivered to the final destination.
+    pub data: Vec<u8>,
+}
+
+impl ForwardEnvelope {
+    /// Creates the envelope of `content`, which is forwarded along `route`.
+    pub fn new(route: Vec<String>, content: IContent) -> Self {
+        Self {
+            route,
+            origin: None,
+            contract: content.contract,
+            action: content.action,
+            data: content.data,
+        }
+    }
+
+    /// Returns the envelope carried by `payload`, or `Error::InvalidEnvelope` if there is none.
+    pub fn from_payload(payload: &MessagePayload) -> Result<Self, Error> {
+        payload.get_item(String::from(ENVELOPE_ITEM))
+            .and_then(|item| item.in_to::<Vec<u8>>())
+            .and_then(|bytes| ForwardEnvelope::decode(&mut bytes.as_slice()).ok())
+            .ok_or(Error::InvalidEnvelope)
+    }
+
+    /// Returns a payload which carries the envelope.
+    pub fn to_payload(& self) -> MessagePayload {
+        let mut msg_payload = MessagePayload::new();
+        msg_payload.push_item(String::from(ENVELOPE_ITEM), MsgDetail::InkU8Array(self.encode()));
+        msg_payload
+    }
+
+    /// Removes the next chain from the route, and returns it.
+    /// Returns `Error::InvalidEnvelope` if the route has run out.
+    pub fn next_hop(&mut self) -> Result<String, Error> {
+        if self.route.is_empty() {
+            return Err(Error::InvalidEnvelope);
+        }
+
+        Ok(self.route.remove(0))
+    }
+}
+
+/// Returns if `sender` is the relay contract on `chain_name`, which is registered with `RELAY_ACTION`.
+pub fn is_relay<T: MultiDestContracts>(contract: &T, chain_name: &str, sender: &[u8]) -> bool {
+    contract.get_dest_contract_info(String::from(chain_name), String::from(RELAY_ACTION))
+        .map_or(false, |(relay, _)| relay == sender)
+}
+
+/// If you want to relay messages forwarded through the chain of the contract, this trait must be implemented.
+pub trait Relay: CrossChainBase + MultiDestContracts {
+    /// Returns if messages from `sender` on `chain_name` are relayed.
+    /// Envelopes forwarded by the relay contracts registered with `RELAY_ACTION` are always relayed, as their origin is set.
+    fn accepts_sender(& self, chain_name: &str, sender: &[u8]) -> bool;
+}
+
+/// Sends `envelope` to the relay contract on `chain_name`.
+fn send_envelope<T: CrossChainBase + MultiDestContracts>(contract: &mut T, chain_name: String, sqos: Vec<ISQoS>, envelope: ForwardEnvelope) -> Result<u128, Error> {
+    let (relay_contract, relay_action) = contract.get_dest_contract_info(chain_name.clone(), String::from(RELAY_ACTION))
+        .ok_or(Error::DestNotRegistered)?;
+
+    let content = IContent::new(relay_contract, relay_action, envelope.to_payload().to_bytes());
+    let message = IRequestMessage::new(chain_name, sqos, content);
+    cross_chain_helper::cross_chain_send_message(contract, message)
+}
+
+/// Sends `request` to its destination through the intermediate chains `via`, in order, and returns the message id of the first hop.
+/// The relay contract on every intermediate chain must be registered with the action `RELAY_ACTION`.
+/// If `via` is not empty, the destination receives the envelope, and should receive it with `cross_chain_receive_routed`.
+pub fn cross_chain_send_routed<T: CrossChainBase + MultiDestContracts>(contract: &mut T, via: Vec<String>, request: IRequestMessage) -> Result<u128, Error> {
+    let mut route = via;
+    if route.is_empty() {
+        return cross_chain_helper::cross_chain_send_message(contract, request);
+    }
+
+    route.push(request.to_chain);
+    let mut envelope = ForwardEnvelope::new(route, request.content);
+    let first_hop = envelope.next_hop()?;
+
+    send_envelope(contract, first_hop, request.sqos, envelope)
+}
+
+/// Unwraps a forwarded message, and sends it to the next chain in its route, returns the message id.
+/// Relay contracts on intermediate chains should call it in the message registered with `RELAY_ACTION`.
+///
+/// The first relay only accepts messages from senders accepted by `Relay::accepts_sender`, and sets their origin in the envelope.
+/// Later relays only accept envelopes from the relay contracts registered with `RELAY_ACTION`, so the origin can not be forged.
+/// Returns `Error::UntrustedSender` otherwise.
+pub fn cross_chain_relay<T: Relay>(contract: &mut T, payload: MessagePayload) -> Result<u128, Error> {
+    let context = cross_chain_helper::cross_chain_receive(contract)?;
+
+    let mut envelope = ForwardEnvelope::from_payload(&payload)?;
+    if envelope.origin.is_none() {
+        if !contract.accepts_sender(&context.from_chain, &context.sender) {
+            return Err(Error::UntrustedSender);
+        }
+        envelope.origin = Some((context.from_chain.clone(), context.sender.clone()));
+    }
+    else if !is_relay(contract, &context.from_chain, &context.sender) {
+        return Err(Error::UntrustedSender);
+    }
+
+    let next_hop = envelope.next_hop()?;
+    if envelope.route.is_empty() {
+        let content = IContent::new(envelope.contract.clone(), envelope.action.clone(), envelope.to_payload().to_bytes());
+        let message = IRequestMessage::new(next_hop, Vec::<ISQoS>::new(), content);
+        cross_chain_helper::cross_chain_send_message(contract, message)
+    }
+    else {
+        send_envelope(contract, next_hop, Vec::<ISQoS>::new(), envelope)
+    }
+}
+
+/// Receives a message forwarded through intermediate chains, and returns the context of the last hop with the envelope.
+/// The `origin` of the envelope is the source chain and sender of the message, and `data` is the data sent.
+/// Returns `Error::UntrustedSender` if the last hop is not the relay contract registered with `RELAY_ACTION` for its chain.
+pub fn cross_chain_receive_routed<T: CrossChainBase + MultiDestContracts>(contract: &mut T, payload: MessagePayload) -> Result<(IContext, ForwardEnvelope), Error> {
+    let context = cross_chain_helper::cross_chain_receive(contract)?;
+
+    let envelope = ForwardEnvelope::from_payload(&payload)?;
+    if !envelope.route.is_empty() || envelope.origin.is_none() {
+        return Err(Error::InvalidEnvelope);
+    }
+    if !is_relay(contract, &context.from_chain, &context.sender) {
+        return Err(Error::UntrustedSender);
+    }
+
+    Ok((context, envelope))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// We test if envelopes are unwrapped hop by hop, and rejected once the route runs out.
+    #[test]
+    fn envelope_works() {
+        let route = ink::prelude::vec![String::from("MOONBEAM"), String::from("ASTAR"), String::from("NEAR")];
+        let content = IContent::new(ink::prelude::vec![0x01; 20], ink::prelude::vec![0x02; 4], ink::prelude::vec![0x03]);
+        let mut envelope = ForwardEnvelope::new(route, content);
+        assert_eq!(envelope.next_hop(), Ok(String::from("MOONBEAM")));
+
+        let mut relayed = ForwardEnvelope::from_payload(&envelope.to_payload()).unwrap();
+        assert_eq!(relayed, envelope);
+        assert_eq!(relayed.next_hop(), Ok(String::from("ASTAR")));
+        assert_eq!(relayed.next_hop(), Ok(String::from("NEAR")));
+        assert_eq!(relayed.data, [0x03]);
+        assert_eq!(relayed.next_hop(), Err(Error::InvalidEnvelope));
+        assert_eq!(ForwardEnvelope::from_payload(&MessagePayload::new()).err(), Some(Error::InvalidEnvelope));
+    }
+}
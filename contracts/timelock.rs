@@ -0,0 +1,115 @@
+use ink::primitives::AccountId;
+use crate::cross_chain_helper::Error;
+
+/// The cross-chain contract address, which can only be rotated after a delay.
+///
+/// A new cross-chain contract is proposed first, and can be applied once `delay` blocks have passed,
+/// so users have time to react to a malicious change. The contract should emit an event of its own at every step.
+#[ink::storage_item]
+#[derive(Default)]
+pub struct CrossChainContractTimelock {
+    current: Option<AccountId>,
+    pending: Option<(AccountId, u32)>,
+    delay: u32,
+}
+
+impl CrossChainContractTimelock {
+    /// Creates the timelock with `delay` blocks between a proposal and its application.
+    pub fn new(delay: u32) -> Self {
+        Self {
+            current: None,
+            pending: None,
+            delay,
+        }
+    }
+
+    /// Returns the cross-chain contract in effect.
+    pub fn current(& self) -> Option<AccountId> {
+        self.current
+    }
+
+    /// Returns the proposed cross-chain contract, and the block number from which it can be applied.
+    pub fn pending(& self) -> Option<(AccountId, u32)> {
+        self.pending
+    }
+
+    /// Returns the number of blocks between a proposal and its application.
+    pub fn delay(& self) -> u32 {
+        self.delay
+    }
+
+    /// Increases the delay, it can not be decreased, which would allow to bypass the timelock.
+    pub fn increase_delay(&mut self, delay: u32) -> Result<(), Error> {
+        if delay < self.delay {
+            return Err(Error::InvalidDelay);
+        }
+        self.delay = delay;
+
+        Ok(())
+    }
+
+    /// Sets the first cross-chain contract, which takes effect immediately.
+    pub fn initialize(&mut self, contract: AccountId) -> Result<(), Error> {
+        if self.current.is_some() {
+            return Err(Error::AlreadyInitialized);
+        }
+        self.current = Some(contract);
+
+        Ok(())
+    }
+
+    /// Proposes `contract` as the new cross-chain contract, replacing the pending proposal if there is one.
+    /// Returns the block number from which it can be applied.
+    pub fn propose(&mut self, contract: AccountId) -> u32 {
+        let now = ink::env::block_number::<ink::env::DefaultEnvironment>();
+        let effective_at = now.saturating_add(self.delay);
+        self.pending = Some((contract, effective_at));
+
+        effective_at
+    }
+
+    /// Applies the proposed cross-chain contract, if the delay has passed.
+    pub fn apply(&mut self) -> Result<AccountId, Error> {
+        let (contract, effective_at) = self.pending.ok_or(Error::NoPendingRotation)?;
+        if ink::env::block_number::<ink::env::DefaultEnvironment>() < effective_at {
+            return Err(Error::RotationNotReady);
+        }
+
+        self.current = Some(contract);
+        self.pending = None;
+
+        Ok(contract)
+    }
+
+    /// Cancels the proposed cross-chain contract, and returns it.
+    pub fn cancel(&mut self) -> Result<AccountId, Error> {
+        let (contract, _) = self.pending.ok_or(Error::NoPendingRotation)?;
+        self.pending = None;
+
+        Ok(contract)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// We test if a proposal can only be applied after the delay.
+    #[ink::test]
+    fn apply_after_delay_works() {
+        let first = AccountId::from([0x01; 32]);
+        let second = AccountId::from([0x02; 32]);
+        let mut timelock = CrossChainContractTimelock::new(2);
+        assert_eq!(timelock.initialize(first), Ok(()));
+        assert_eq!(timelock.initialize(second), Err(Error::AlreadyInitialized));
+
+        timelock.propose(second);
+        assert_eq!(timelock.apply(), Err(Error::RotationNotReady));
+        ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+        ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+        assert_eq!(timelock.apply(), Ok(second));
+        assert_eq!(timelock.current(), Some(second));
+        assert_eq!(timelock.cancel(), Err(Error::NoPendingRotation));
+        assert_eq!(timelock.increase_delay(1), Err(Error::InvalidDelay));
+    }
+}
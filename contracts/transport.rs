@@ -0,0 +1,225 @@
+use ink::primitives::AccountId;
+use ink::prelude::string::String;
+use payload::message_define::{
+    ISentMessage,
+    ISQoS,
+    IContext,
+};
+use crate::cross_chain_helper::Error;
+
+#[cfg(not(any(feature = "protocol-v1")))]
+compile_error!("a protocol version of the cross-chain contract must be selected, e.g. feature `protocol-v1`");
+
+/// Version of the cross-chain contract protocol the selectors are derived for.
+#[cfg(feature = "protocol-v1")]
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Selectors of the cross-chain contract, derived from its message names.
+#[cfg(feature = "protocol-v1")]
+mod selectors {
+    pub const SEND_MESSAGE: [u8; 4] = ink::selector_bytes!("CrossChainBase::send_message");
+    pub const GET_CONTEXT: [u8; 4] = ink::selector_bytes!("CrossChainBase::get_context");
+    pub const GET_SQOS: [u8; 4] = ink::selector_bytes!("get_sqos");
+    pub const SET_SQOS: [u8; 4] = ink::selector_bytes!("set_sqos");
+    pub const REMOVE_SQOS: [u8; 4] = ink::selector_bytes!("remove_sqos");
+    #[cfg(feature = "sent-message-query")]
+    pub const GET_SENT_MESSAGE: [u8; 4] = ink::selector_bytes!("CrossChainBase::get_sent_message");
+}
+
+/// The way the helpers talk to a cross-chain contract.
+/// Implement it to plug in another transport, and return it with `CrossChainBase::get_transport`.
+pub trait CrossChainTransport {
+    /// Sends `message` through the cross-chain contract `cross_chain`, and returns the message id.
+    fn send_message(& self, cross_chain: AccountId, message: ISentMessage) -> Result<u128, Error>;
+
+    /// Returns context of the message being delivered by the cross-chain contract `cross_chain`.
+    fn get_context(& self, cross_chain: AccountId) -> Option<IContext>;
+
+    /// Returns SQoS of `account_id` registered in the cross-chain contract `cross_chain`.
+    fn get_sqos(& self, cross_chain: AccountId, account_id: AccountId) -> Result<Option<ISQoS>, Error>;
+
+    /// Sets SQoS of `account_id` registered in the cross-chain contract `cross_chain`.
+    fn set_sqos(& self, cross_chain: AccountId, sqos: ISQoS, account_id: AccountId) -> Result<(), Error>;
+
+    /// Removes SQoS of `account_id` registered in the cross-chain contract `cross_chain`.
+    fn remove_sqos(& self, cross_chain: AccountId, account_id: AccountId) -> Result<(), Error>;
+
+    /// Returns the message `id` sent to `to_chain`, recorded in the cross-chain contract `cross_chain`.
+    /// Transports which can not query sent messages return `None`.
+    fn get_sent_message(& self, _cross_chain: AccountId, _to_chain: String, _id: u128) -> Result<Option<ISentMessage>, Error> {
+        Ok(None)
+    }
+}
+
+/// The transport of the Dante cross-chain contract, it is used by default.
+pub struct DanteTransport;
+
+impl CrossChainTransport for DanteTransport {
+    fn send_message(& self, cross_chain: AccountId, message: ISentMessage) -> Result<u128, Error> {
+        ink::env::call::build_call::<ink::env::DefaultEnvironment>()
+                .call_type(
+                    ink::env::call::Call::new()
+                        .callee(cross_chain)
+                        .gas_limit(0)
+                        .transferred_value(0))
+                .exec_input(
+                    ink::env::call::ExecutionInput::new(ink::env::call::Selector::new(selectors::SEND_MESSAGE))
+                    .push_arg(message)
+                )
+                .returns::<u128>()
+                .fire()
+                .map_err(|_| Error::CallFailed)
+    }
+
+    fn get_context(& self, cross_chain: AccountId) -> Option<IContext> {
+        ink::env::call::build_call::<ink::env::DefaultEnvironment>()
+                .call_type(
+                    ink::env::call::Call::new()
+                        .callee(cross_chain)
+                        .gas_limit(0)
+                        .transferred_value(0))
+                .exec_input(
+                    ink::env::call::ExecutionInput::new(ink::env::call::Selector::new(selectors::GET_CONTEXT))
+                )
+                .returns::<Option<IContext>>()
+                .fire()
+                .unwrap()
+    }
+
+    fn get_sqos(& self, cross_chain: AccountId, account_id: AccountId) -> Result<Option<ISQoS>, Error> {
+        ink::env::call::build_call::<ink::env::DefaultEnvironment>()
+                .call_type(
+                    ink::env::call::Call::new()
+                        .callee(cross_chain)
+                        .gas_limit(0)
+                        .transferred_value(0))
+                .exec_input(
+                    ink::env::call::ExecutionInput::new(ink::env::call::Selector::new(selectors::GET_SQOS))
+                    .push_arg(account_id)
+                )
+                .returns::<Option<ISQoS>>()
+                .fire()
+                .map_err(|_| Error::CallFailed)
+    }
+
+    fn set_sqos(& self, cross_chain: AccountId, sqos: ISQoS, account_id: AccountId) -> Result<(), Error> {
+        ink::env::call::build_call::<ink::env::DefaultEnvironment>()
+                .call_type(
+                    ink::env::call::Call::new()
+                        .callee(cross_chain)
+                        .gas_limit(0)
+                        .transferred_value(0))
+                .exec_input(
+                    ink::env::call::ExecutionInput::new(ink::env::call::Selector::new(selectors::SET_SQOS))
+                    .push_arg(account_id)
+                    .push_arg(sqos)
+                )
+                .returns::<()>()
+                .fire()
+                .map_err(|_| Error::CallFailed)
+    }
+
+    fn remove_sqos(& self, cross_chain: AccountId, account_id: AccountId) -> Result<(), Error> {
+        ink::env::call::build_call::<ink::env::DefaultEnvironment>()
+                .call_type(
+                    ink::env::call::Call::new()
+                        .callee(cross_chain)
+                        .gas_limit(0)
+                        .transferred_value(0))
+                .exec_input(
+                    ink::env::call::ExecutionInput::new(ink::env::call::Selector::new(selectors::REMOVE_SQOS))
+                    .push_arg(account_id)
+                )
+                .returns::<()>()
+                .fire()
+                .map_err(|_| Error::CallFailed)
+    }
+
+    #[cfg(feature = "sent-message-query")]
+    fn get_sent_message(& self, cross_chain: AccountId, to_chain: String, id: u128) -> Result<Option<ISentMessage>, Error> {
+        ink::env::call::build_call::<ink::env::DefaultEnvironment>()
+                .call_type(
+                    ink::env::call::Call::new()
+                        .callee(cross_chain)
+                        .gas_limit(0)
+                        .transferred_value(0))
+                .exec_input(
+                    ink::env::call::ExecutionInput::new(ink::env::call::Selector::new(selectors::GET_SENT_MESSAGE))
+                    .push_arg(to_chain)
+                    .push_arg(id)
+                )
+                .returns::<Option<ISentMessage>>()
+                .fire()
+                .map_err(|_| Error::CallFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ink::prelude::vec::Vec;
+    use payload::message_define::{
+        IContent,
+        IRequestMessage,
+    };
+    use crate::cross_chain_helper::{
+        self,
+        CrossChainBase,
+    };
+    use crate::message_status::MessageStatus;
+    use crate::mock::MockTransport;
+
+    #[derive(Default)]
+    struct MockContract {
+        transport: MockTransport,
+    }
+
+    impl CrossChainBase for MockContract {
+        fn get_cross_chain_contract_address(& self) -> AccountId {
+            AccountId::from([0x01; 32])
+        }
+
+        fn get_transport(& self) -> &dyn CrossChainTransport {
+            &self.transport
+        }
+    }
+
+    /// We test if the derived selectors match the ones the deployed cross-chain contract uses.
+    #[cfg(feature = "protocol-v1")]
+    #[test]
+    fn protocol_v1_selectors_work() {
+        assert_eq!(selectors::SEND_MESSAGE, [0x27, 0x26, 0x79, 0x17]);
+        assert_eq!(selectors::GET_CONTEXT, [0xee, 0xe9, 0xc1, 0xb3]);
+        assert_eq!(selectors::GET_SQOS, [0x8d, 0xe9, 0x09, 0xd7]);
+        assert_eq!(selectors::SET_SQOS, [0xc1, 0xe9, 0xbc, 0xda]);
+        assert_eq!(selectors::REMOVE_SQOS, [0x35, 0x2b, 0x71, 0xb5]);
+    }
+
+    /// We test if the derived selector of the sent-message query is pinned.
+    #[cfg(all(feature = "protocol-v1", feature = "sent-message-query"))]
+    #[test]
+    fn protocol_v1_sent_message_selector_works() {
+        assert_eq!(selectors::GET_SENT_MESSAGE, [0x2f, 0x8e, 0x34, 0xc8]);
+    }
+
+    /// We test if the helpers dispatch through the transport of the contract.
+    #[ink::test]
+    fn helpers_use_transport() {
+        let mut contract = MockContract::default();
+        let content = IContent::new(Vec::<u8>::new(), Vec::<u8>::new(), Vec::<u8>::new());
+        let request = IRequestMessage::new(String::from("ETHEREUM"), Vec::<ISQoS>::new(), content);
+        assert_eq!(cross_chain_helper::cross_chain_send_message(&mut contract, request), Ok(1));
+        assert!(cross_chain_helper::get_context(&contract).is_none());
+    }
+
+    /// We test if the status of a message is queried from the cross-chain contract, if there is no local record.
+    #[ink::test]
+    fn message_status_query_works() {
+        let mut contract = MockContract::default();
+        let content = IContent::new(Vec::<u8>::new(), Vec::<u8>::new(), Vec::<u8>::new());
+        let request = IRequestMessage::new(String::from("ETHEREUM"), Vec::<ISQoS>::new(), content);
+        assert_eq!(cross_chain_helper::cross_chain_send_message(&mut contract, request), Ok(1));
+        assert_eq!(cross_chain_helper::get_message_status(&contract, String::from("Ethereum"), 1), Some(MessageStatus::Sent));
+        assert_eq!(cross_chain_helper::get_message_status(&contract, String::from("ETHEREUM"), 2), None);
+    }
+}
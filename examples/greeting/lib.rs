@@ -5,7 +5,17 @@ mod greeting {
     use ink::prelude::string::String;
     use ink::prelude::vec::Vec;
     use ink::storage::Mapping;
-    use ink_sdk::{cross_chain_helper, CrossChainSQoS, MultiDestContracts, Ownable};
+    use ink_sdk::{
+        cross_chain_helper, CrossChainSQoS, Direction, ManageChains, ManageDeadLetters,
+        MultiDestContracts, Ownable, PauseState, Pausable,
+    };
+    use ink_sdk::chain_registry::{self, ChainInfo, ChainRegistry};
+    use ink_sdk::dead_letter::{self, DeadLetter, DeadLetterHandling, DeadLetterQueue};
+    use ink_sdk::limits::{PayloadLimits, PayloadLimitsConfig};
+    use ink_sdk::remote_address;
+    use ink_sdk::replay_guard::ReplayGuard;
+    use ink_sdk::result_store::ResultStore;
+    use ink_sdk::timelock::CrossChainContractTimelock;
     use payload::message_define::{IContent, IContext, IRequestMessage, ISQoS};
     use payload::message_protocol::{MessagePayload, MsgDetail};
 
@@ -13,6 +23,76 @@ mod greeting {
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum Error {
         MethodNotRegisterd,
+        NotOwner,
+        InvalidPayload,
+        CrossChainError(cross_chain_helper::Error),
+    }
+
+    /// Number of blocks before a proposed cross-chain contract takes effect, about one day.
+    const CROSS_CHAIN_CONTRACT_DELAY: BlockNumber = 14400;
+
+    impl From<cross_chain_helper::Error> for Error {
+        fn from(error: cross_chain_helper::Error) -> Self {
+            Error::CrossChainError(error)
+        }
+    }
+
+    /// Emitted when a new cross-chain contract is proposed.
+    #[ink(event)]
+    pub struct CrossChainContractProposed {
+        /// The proposed cross-chain contract.
+        #[ink(topic)]
+        contract: AccountId,
+        /// Block number from which the proposal can be applied.
+        effective_at: BlockNumber,
+    }
+
+    /// Emitted when a proposed cross-chain contract takes effect.
+    #[ink(event)]
+    pub struct CrossChainContractApplied {
+        /// The previous cross-chain contract.
+        previous: Option<AccountId>,
+        /// The new cross-chain contract.
+        #[ink(topic)]
+        contract: AccountId,
+    }
+
+    /// Emitted when a proposed cross-chain contract is cancelled.
+    #[ink(event)]
+    pub struct CrossChainContractCancelled {
+        /// The cancelled cross-chain contract.
+        #[ink(topic)]
+        contract: AccountId,
+    }
+
+    /// Emitted when a cross-chain message is sent.
+    #[ink(event)]
+    pub struct MessageSent {
+        /// Destination chain.
+        #[ink(topic)]
+        chain: String,
+        /// Message id.
+        #[ink(topic)]
+        id: u128,
+        /// Destination action.
+        action: Vec<u8>,
+        /// Session type, see `session_type` of `ISession`.
+        session_type: u8,
+    }
+
+    /// Emitted when a cross-chain message is received.
+    #[ink(event)]
+    pub struct MessageReceived {
+        /// Source chain.
+        #[ink(topic)]
+        chain: String,
+        /// Message id.
+        #[ink(topic)]
+        id: u128,
+        /// Destination action.
+        action: Vec<u8>,
+        /// Session type, see `session_type` of `ISession`.
+        session_type: u8,
     }
 
     /// Defines the storage of your contract.
@@ -23,15 +103,54 @@ mod greeting {
     pub struct Greeting {
         /// Account id of owner
         owner: Option<AccountId>,
-        cross_chain_contract: Option<AccountId>,
-        ret: Mapping<(String, u128), String>,
+        cross_chain_contract: CrossChainContractTimelock,
+        ret: ResultStore<Vec<String>>,
         dest_contract_map: Mapping<(String, String), (Vec<u8>, Vec<u8>)>,
+        pause_state: PauseState,
+        replay_guard: ReplayGuard,
+        dead_letters: DeadLetterQueue,
+        chain_registry: ChainRegistry,
+        payload_limits: PayloadLimitsConfig,
     }
 
     /// We use `CrossChainBase` here, to be able to use the sdk functionalities.
     impl cross_chain_helper::CrossChainBase for Greeting {
         fn get_cross_chain_contract_address(&self) -> AccountId {
-            self.cross_chain_contract.unwrap()
+            self.cross_chain_contract.current().unwrap()
+        }
+
+        fn get_pause_state(&self) -> Option<&PauseState> {
+            Some(&self.pause_state)
+        }
+
+        fn get_replay_guard_mut(&mut self) -> Option<&mut ReplayGuard> {
+            Some(&mut self.replay_guard)
+        }
+
+        fn get_chain_registry(&self) -> Option<&ChainRegistry> {
+            Some(&self.chain_registry)
+        }
+
+        fn get_payload_limits(&self, chain_name: &str) -> Option<PayloadLimits> {
+            self.payload_limits.get(chain_name)
+        }
+
+        fn on_message_sent(&mut self, chain_name: &str, id: u128, action: &[u8], session_type: u8) {
+            self.env().emit_event(MessageSent {
+                chain: String::from(chain_name),
+                id,
+                action: Vec::from(action),
+                session_type,
+            });
+        }
+
+        fn on_message_received(&mut self, context: &IContext) {
+            self.env().emit_event(MessageReceived {
+                chain: context.from_chain.clone(),
+                id: context.id,
+                action: Vec::from(context.action),
+                session_type: context.session.session_type,
+            });
         }
     }
 
@@ -72,6 +191,7 @@ mod greeting {
             chain_name: String,
             action: String,
         ) -> Option<(Vec<u8>, Vec<u8>)> {
+            let chain_name = chain_registry::normalize(&chain_name);
             self.dest_contract_map.get((chain_name, action))
         }
 
@@ -84,6 +204,8 @@ mod greeting {
             dest_action: Vec<u8>,
         ) -> Result<(), u8> {
             self.only_owner()?;
+            let chain_name = chain_registry::canonical_chain_name(self, &chain_name).map_err(|_| 2)?;
+            remote_address::validate_dest_contract(self, &chain_name, &contract).map_err(|_| 2)?;
 
             self.dest_contract_map
                 .insert((chain_name, action), &(contract, dest_action));
@@ -92,27 +214,113 @@ mod greeting {
         }
     }
 
+    /// We only talk to registered chains.
+    impl ManageChains for Greeting {
+        #[ink(message)]
+        fn get_chain(&self, chain_name: String) -> Option<ChainInfo> {
+            self.chain_registry.get(&chain_name)
+        }
+
+        #[ink(message)]
+        fn register_chain(&mut self, chain_name: String, info: ChainInfo) -> Result<(), u8> {
+            self.only_owner()?;
+
+            self.chain_registry.register(&chain_name, info).map_err(|_| 2)
+        }
+
+        #[ink(message)]
+        fn unregister_chain(&mut self, chain_name: String) -> Result<(), u8> {
+            self.only_owner()?;
+
+            self.chain_registry.unregister(&chain_name);
+
+            Ok(())
+        }
+    }
+
+    /// We use `Pausable` here, to be able to halt messages if a peer contract is compromised.
+    impl Pausable for Greeting {
+        #[ink(message)]
+        fn paused(&self, direction: Direction, chain_name: Option<String>) -> bool {
+            match chain_name {
+                Some(chain_name) => self.pause_state.is_paused(direction, &chain_registry::normalize(&chain_name)),
+                None => self.pause_state.is_paused_for_all(direction),
+            }
+        }
+
+        #[ink(message)]
+        fn pause(&mut self, direction: Direction, chain_name: Option<String>) -> Result<(), u8> {
+            self.only_owner()?;
+
+            let chain_name = chain_name.map(|chain_name| chain_registry::normalize(&chain_name));
+            self.pause_state.set_paused(direction, chain_name, true);
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn unpause(&mut self, direction: Direction, chain_name: Option<String>) -> Result<(), u8> {
+            self.only_owner()?;
+
+            let chain_name = chain_name.map(|chain_name| chain_registry::normalize(&chain_name));
+            self.pause_state.set_paused(direction, chain_name, false);
+
+            Ok(())
+        }
+    }
+
+    /// We keep greetings which can not be handled as dead letters.
+    impl DeadLetterHandling for Greeting {
+        fn get_dead_letter_queue_mut(&mut self) -> &mut DeadLetterQueue {
+            &mut self.dead_letters
+        }
+    }
+
+    /// We use `ManageDeadLetters` here, to be able to inspect, replay or discard dead letters.
+    impl ManageDeadLetters for Greeting {
+        #[ink(message)]
+        fn get_dead_letter(&self, id: u128) -> Option<DeadLetter> {
+            self.dead_letters.get(id)
+        }
+
+        #[ink(message)]
+        fn replay_dead_letter(&mut self, id: u128) -> Result<(), u8> {
+            self.only_owner()?;
+
+            dead_letter::replay_dead_letter(self, id, Self::handle_greeting).map_err(|_| 2)
+        }
+
+        #[ink(message)]
+        fn discard_dead_letter(&mut self, id: u128) -> Result<(), u8> {
+            self.only_owner()?;
+
+            dead_letter::discard_dead_letter(self, id).map_err(|_| 2)
+        }
+    }
+
     /// We use `CrossChainSQoS` here, because
     impl CrossChainSQoS for Greeting {
         /// Inserts one SQoS item.
         /// If the item exists, it will be replaced.
         #[ink(message)]
-        fn set_sqos(&mut self, sqos_item: ISQoS) {
+        fn set_sqos(&mut self, sqos_item: ISQoS) -> Result<(), u8> {
             // self.only_owner()?;
 
             let account_id = Self::env().account_id();
-            cross_chain_helper::set_sqos(self, sqos_item, account_id);
+            cross_chain_helper::set_sqos(self, sqos_item, account_id).map_err(|_| 2)
         }
 
         /// Removes one SQoS item.
         #[ink(message)]
-        fn remove_sqos(&mut self) {
+        fn remove_sqos(&mut self) -> Result<(), u8> {
             // self.only_owner()?;
 
             let account_id = Self::env().account_id();
             if let Some(_) = cross_chain_helper::get_sqos(self, account_id) {
-                cross_chain_helper::remove_sqos(self, account_id);
+                cross_chain_helper::remove_sqos(self, account_id).map_err(|_| 2)?;
             }
+
+            Ok(())
         }
 
         /// Returns SQoS items
@@ -128,9 +336,14 @@ mod greeting {
         pub fn new() -> Self {
             Self {
                 owner: Some(Self::env().caller()),
-                cross_chain_contract: None,
+                cross_chain_contract: CrossChainContractTimelock::new(CROSS_CHAIN_CONTRACT_DELAY),
                 ret: Default::default(),
                 dest_contract_map: Default::default(),
+                pause_state: Default::default(),
+                replay_guard: Default::default(),
+                dead_letters: Default::default(),
+                chain_registry: Default::default(),
+                payload_limits: Default::default(),
             }
         }
 
@@ -140,20 +353,72 @@ mod greeting {
         //     self.owner = Some(caller);
         // }
 
-        /// Sets cross-chain contract address
+        /// Sets the first cross-chain contract address, later changes must be proposed
         #[ink(message)]
-        pub fn set_cross_chain_contract(&mut self, contract: AccountId) {
-            // self.only_owner()?;
+        pub fn set_cross_chain_contract(&mut self, contract: AccountId) -> Result<(), Error> {
+            self.only_owner().map_err(|_| Error::NotOwner)?;
 
-            self.cross_chain_contract = Some(contract);
+            self.cross_chain_contract.initialize(contract)?;
 
-            // Ok(())
+            Ok(())
+        }
+
+        /// Proposes a new cross-chain contract address, which can be applied after a delay
+        #[ink(message)]
+        pub fn propose_cross_chain_contract(&mut self, contract: AccountId) -> Result<BlockNumber, Error> {
+            self.only_owner().map_err(|_| Error::NotOwner)?;
+
+            let effective_at = self.cross_chain_contract.propose(contract);
+            self.env().emit_event(CrossChainContractProposed {
+                contract,
+                effective_at,
+            });
+
+            Ok(effective_at)
+        }
+
+        /// Applies the proposed cross-chain contract address, if the delay has passed
+        #[ink(message)]
+        pub fn apply_cross_chain_contract(&mut self) -> Result<(), Error> {
+            self.only_owner().map_err(|_| Error::NotOwner)?;
+
+            let previous = self.cross_chain_contract.current();
+            let contract = self.cross_chain_contract.apply()?;
+            self.env().emit_event(CrossChainContractApplied {
+                previous,
+                contract,
+            });
+
+            Ok(())
+        }
+
+        /// Cancels the proposed cross-chain contract address
+        #[ink(message)]
+        pub fn cancel_cross_chain_contract(&mut self) -> Result<(), Error> {
+            self.only_owner().map_err(|_| Error::NotOwner)?;
+
+            let contract = self.cross_chain_contract.cancel()?;
+            self.env().emit_event(CrossChainContractCancelled {
+                contract,
+            });
+
+            Ok(())
+        }
+
+        /// Sets the payload limits of `chain_name`, or the default ones if `chain_name` is `None`
+        #[ink(message)]
+        pub fn set_payload_limits(&mut self, chain_name: Option<String>, limits: Option<PayloadLimits>) -> Result<(), Error> {
+            self.only_owner().map_err(|_| Error::NotOwner)?;
+
+            self.payload_limits.set(chain_name, limits);
+
+            Ok(())
         }
 
         #[ink(message)]
         pub fn clear_ret(&mut self, chain_name: String, id: u128) -> Result<(), u8>{
             self.only_owner()?;
-            self.ret.remove(&(chain_name, id));
+            self.ret.remove(&chain_registry::normalize(&chain_name), id);
             Ok(())
         }
 
@@ -195,31 +460,33 @@ mod greeting {
             let content = IContent::new(contract, action, data);
             let message = IRequestMessage::new(chain_name, sqos, content);
 
-            cross_chain_helper::cross_chain_send_message(self, message);
+            cross_chain_helper::cross_chain_send_message(self, message)?;
 
             Ok(())
         }
 
         /// Receives greeting from another chain
+        /// If the greeting can not be handled, it is kept as a dead letter, and its id is returned
         #[ink(message)]
-        pub fn receive_greeting(&mut self, payload: MessagePayload) -> String {
+        pub fn receive_greeting(&mut self, payload: MessagePayload) -> Result<Option<u128>, Error> {
+            Ok(dead_letter::cross_chain_receive_or_dead_letter(self, payload, Self::handle_greeting)?)
+        }
+
+        /// Handles greeting received from another chain
+        fn handle_greeting(&mut self, context: &IContext, payload: &MessagePayload) -> Result<(), Error> {
             let item = payload
                 .get_item(String::try_from("greeting").unwrap())
-                .unwrap();
+                .ok_or(Error::InvalidPayload)?;
             // let param: Vec<String> = scale::Decode::decode(&mut item.v.as_slice()).unwrap();
-            let param = item.in_to::<Vec<String>>();
-            let context: IContext = cross_chain_helper::get_context(self).unwrap();
-            // let payload
-            let mut s = String::new();
-            s = s + &ink::prelude::format!("{:?}", param);
-            self.ret.insert((context.from_chain, context.id), &s);
-            s
+            let param = item.in_to::<Vec<String>>().ok_or(Error::InvalidPayload)?;
+            self.ret.insert(&context.from_chain, context.id, &param)?;
+            Ok(())
         }
 
         /// Receives message from another chain
         #[ink(message)]
-        pub fn get_ret(&self, key: (String, u128)) -> String {
-            self.ret.get(key).unwrap_or(String::from("No value"))
+        pub fn get_ret(&self, key: (String, u128)) -> Option<Vec<String>> {
+            self.ret.get(&chain_registry::normalize(&key.0), key.1)
         }
     }
 
@@ -236,7 +503,7 @@ mod greeting {
         fn set_cross_chain_contract_works() {
             let mut locker = Greeting::new();
             let contract_id = ink::env::test::callee::<ink::env::DefaultEnvironment>();
-            locker.set_cross_chain_contract(contract_id);
+            locker.set_cross_chain_contract(contract_id).unwrap();
         }
     }
 }
@@ -5,8 +5,15 @@ mod os_computing {
     use ink_sdk::{
         Ownable,
         MultiDestContracts,
+        DestEncodings,
         cross_chain_helper,
     };
+    use ink_sdk::chain_registry;
+    use ink_sdk::context::{self, CrossChainContext};
+    use ink_sdk::remote_address::{self, ChainFamily};
+    use ink_sdk::codec::PayloadEncoding;
+    use ink_sdk::result_store::ResultStore;
+    use ink_sdk::timelock::CrossChainContractTimelock;
     use ink::prelude::string::String;
     use ink::prelude::vec::Vec;
     use payload::message_define::{
@@ -29,6 +36,75 @@ mod os_computing {
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum Error {
         MethodNotRegisterd,
+        NotOwner,
+        CrossChainError(cross_chain_helper::Error),
+    }
+
+    /// Number of blocks before a proposed cross-chain contract takes effect, about one day.
+    const CROSS_CHAIN_CONTRACT_DELAY: BlockNumber = 14400;
+
+    impl From<cross_chain_helper::Error> for Error {
+        fn from(error: cross_chain_helper::Error) -> Self {
+            Error::CrossChainError(error)
+        }
+    }
+
+    /// Emitted when a new cross-chain contract is proposed.
+    #[ink(event)]
+    pub struct CrossChainContractProposed {
+        /// The proposed cross-chain contract.
+        #[ink(topic)]
+        contract: AccountId,
+        /// Block number from which the proposal can be applied.
+        effective_at: BlockNumber,
+    }
+
+    /// Emitted when a proposed cross-chain contract takes effect.
+    #[ink(event)]
+    pub struct CrossChainContractApplied {
+        /// The previous cross-chain contract.
+        previous: Option<AccountId>,
+        /// The new cross-chain contract.
+        #[ink(topic)]
+        contract: AccountId,
+    }
+
+    /// Emitted when a proposed cross-chain contract is cancelled.
+    #[ink(event)]
+    pub struct CrossChainContractCancelled {
+        /// The cancelled cross-chain contract.
+        #[ink(topic)]
+        contract: AccountId,
+    }
+
+    /// Emitted when a cross-chain message is sent.
+    #[ink(event)]
+    pub struct MessageSent {
+        /// Destination chain.
+        #[ink(topic)]
+        chain: String,
+        /// Message id.
+        #[ink(topic)]
+        id: u128,
+        /// Destination action.
+        action: Vec<u8>,
+        /// Session type, see `session_type` of `ISession`.
+        session_type: u8,
+    }
+
+    /// Emitted when a cross-chain message is received.
+    #[ink(event)]
+    pub struct MessageReceived {
+        /// Source chain.
+        #[ink(topic)]
+        chain: String,
+        /// Message id.
+        #[ink(topic)]
+        id: u128,
+        /// Destination action.
+        action: Vec<u8>,
+        /// Session type, see `session_type` of `ISession`.
+        session_type: u8,
     }
 
     /// Defines the storage of your contract.
@@ -39,15 +115,44 @@ mod os_computing {
     pub struct OSComputing {
         /// Account id of owner
         owner: Option<AccountId>,
-        cross_chain_contract: Option<AccountId>,
-        ret: Mapping<(String, u128), String>,
+        cross_chain_contract: CrossChainContractTimelock,
+        ret: ResultStore<u32>,
         dest_contract_map: Mapping<(String, String), (Vec<u8>, Vec<u8>)>,
+        dest_encoding_map: Mapping<(String, String), PayloadEncoding>,
     }
 
     /// We use `CrossChainBase` here, to be able to use the sdk functionalities.
     impl cross_chain_helper::CrossChainBase for OSComputing {
         fn get_cross_chain_contract_address(& self) -> AccountId {
-            self.cross_chain_contract.unwrap()
+            self.cross_chain_contract.current().unwrap()
+        }
+
+        fn get_chain_family(& self, chain_name: &str) -> Option<ChainFamily> {
+            match chain_name {
+                "ETHEREUM" | "AVALANCHE" | "MOONBEAM" => Some(ChainFamily::Evm),
+                "NEAR" => Some(ChainFamily::Near),
+                "FLOW" => Some(ChainFamily::Flow),
+                "POLKADOT" | "KUSAMA" => Some(ChainFamily::Substrate),
+                _ => None,
+            }
+        }
+
+        fn on_message_sent(&mut self, chain_name: &str, id: u128, action: &[u8], session_type: u8) {
+            self.env().emit_event(MessageSent {
+                chain: String::from(chain_name),
+                id,
+                action: Vec::from(action),
+                session_type,
+            });
+        }
+
+        fn on_message_received(&mut self, context: &IContext) {
+            self.env().emit_event(MessageReceived {
+                chain: context.from_chain.clone(),
+                id: context.id,
+                action: Vec::from(context.action),
+                session_type: context.session.session_type,
+            });
         }
     }
 
@@ -84,12 +189,15 @@ mod os_computing {
     impl MultiDestContracts for OSComputing {      
         #[ink(message)]  
         fn get_dest_contract_info(& self, chain_name: String, action: String) -> Option<(Vec<u8>, Vec<u8>)> {
+            let chain_name = chain_registry::normalize(&chain_name);
             self.dest_contract_map.get((chain_name, action))
         }
 
         #[ink(message)]
         fn register_dest_contract(&mut self, chain_name: String, action: String, contract: Vec<u8>, dest_action: Vec<u8>) -> Result<(), u8> {
             self.only_owner()?;
+            let chain_name = chain_registry::canonical_chain_name(self, &chain_name).map_err(|_| 2)?;
+            remote_address::validate_dest_contract(self, &chain_name, &contract).map_err(|_| 2)?;
 
             self.dest_contract_map.insert((chain_name, action), &(contract, dest_action));
 
@@ -97,14 +205,34 @@ mod os_computing {
         }
     }
 
+    /// We use `DestEncodings` of SDK here, to be able to send computing tasks to Near contracts which take JSON.
+    impl DestEncodings for OSComputing {
+        #[ink(message)]
+        fn get_dest_encoding(& self, chain_name: String, action: String) -> PayloadEncoding {
+            let chain_name = chain_registry::normalize(&chain_name);
+            self.dest_encoding_map.get((chain_name, action)).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        fn set_dest_encoding(&mut self, chain_name: String, action: String, encoding: PayloadEncoding) -> Result<(), u8> {
+            self.only_owner()?;
+
+            let chain_name = chain_registry::normalize(&chain_name);
+            self.dest_encoding_map.insert((chain_name, action), &encoding);
+
+            Ok(())
+        }
+    }
+
     impl OSComputing {
         #[ink(constructor)]
         pub fn new() -> Self {
             Self {
                 owner: Some(Self::env().caller()),
-                cross_chain_contract: None,
+                cross_chain_contract: CrossChainContractTimelock::new(CROSS_CHAIN_CONTRACT_DELAY),
                 ret: Default::default(),
                 dest_contract_map: Default::default(),
+                dest_encoding_map: Default::default(),
             }
         }
 
@@ -114,12 +242,54 @@ mod os_computing {
         //     self.owner = Some(caller);
         // }
 
-        /// Sets cross-chain contract address
+        /// Sets the first cross-chain contract address, later changes must be proposed
         #[ink(message)]
-        pub fn set_cross_chain_contract(&mut self, contract: AccountId) -> Result<(), u8> {
-            self.only_owner()?;
+        pub fn set_cross_chain_contract(&mut self, contract: AccountId) -> Result<(), Error> {
+            self.only_owner().map_err(|_| Error::NotOwner)?;
+
+            self.cross_chain_contract.initialize(contract)?;
+
+            Ok(())
+        }
+
+        /// Proposes a new cross-chain contract address, which can be applied after a delay
+        #[ink(message)]
+        pub fn propose_cross_chain_contract(&mut self, contract: AccountId) -> Result<BlockNumber, Error> {
+            self.only_owner().map_err(|_| Error::NotOwner)?;
+
+            let effective_at = self.cross_chain_contract.propose(contract);
+            self.env().emit_event(CrossChainContractProposed {
+                contract,
+                effective_at,
+            });
+
+            Ok(effective_at)
+        }
+
+        /// Applies the proposed cross-chain contract address, if the delay has passed
+        #[ink(message)]
+        pub fn apply_cross_chain_contract(&mut self) -> Result<(), Error> {
+            self.only_owner().map_err(|_| Error::NotOwner)?;
+
+            let previous = self.cross_chain_contract.current();
+            let contract = self.cross_chain_contract.apply()?;
+            self.env().emit_event(CrossChainContractApplied {
+                previous,
+                contract,
+            });
+
+            Ok(())
+        }
+
+        /// Cancels the proposed cross-chain contract address
+        #[ink(message)]
+        pub fn cancel_cross_chain_contract(&mut self) -> Result<(), Error> {
+            self.only_owner().map_err(|_| Error::NotOwner)?;
 
-            self.cross_chain_contract = Some(contract);
+            let contract = self.cross_chain_contract.cancel()?;
+            self.env().emit_event(CrossChainContractCancelled {
+                contract,
+            });
 
             Ok(())
         }
@@ -149,14 +319,15 @@ mod os_computing {
             let content = IContent::new(contract, action, data);
             let message = IRequestMessage::new(chain_name, sqos, content);
 
-            cross_chain_helper::cross_chain_call(self, message, 2_u32.to_be_bytes());
+            cross_chain_helper::cross_chain_call(self, message, 2_u32.to_be_bytes())?;
 
             Ok(())
         }
 
         /// Receives computing task from another chain 
         #[ink(message, selector = 1)]
-        pub fn receive_computing_task(&mut self, payload: MessagePayload) -> String {
+        pub fn receive_computing_task(&mut self, payload: MessagePayload) -> Result<String, Error> {
+            cross_chain_helper::cross_chain_receive(self)?;
             let item = payload.get_item(String::try_from("nums").unwrap()).unwrap();
             // let nums: Vec<u32> = scale::Decode::decode(&mut item.v.as_slice()).unwrap();
             let nums = item.in_to::<Vec<u32>>().unwrap();
@@ -172,29 +343,26 @@ mod os_computing {
 
             let sqos = Vec::<ISQoS>::new();
             let message = IResponseMessage::new(sqos, data);
-            cross_chain_helper::cross_chain_respond(self, message);
+            cross_chain_helper::cross_chain_respond(self, message)?;
 
-            String::try_from("Ok").unwrap()
+            Ok(String::try_from("Ok").unwrap())
         }
 
         /// Receives computing task from another chain 
         #[ink(message, selector = 2)]
-        pub fn receive_computing_task_callback(&mut self, payload: MessagePayload) -> String {
+        pub fn receive_computing_task_callback(&mut self, payload: MessagePayload) -> Result<String, Error> {
+            let context: CrossChainContext = context::cross_chain_receive_context(self)?;
             let item = payload.get_item(String::try_from("result").unwrap()).unwrap();
             // let param: u32 = scale::Decode::decode(&mut item.v.as_slice()).unwrap();
             let param = item.in_to::<u32>().unwrap();
-            let context: IContext = cross_chain_helper::get_context(self).unwrap();
-            // let payload
-            let mut s = String::new();
-            s = s + &ink::prelude::format!("{:?}", param);
-            self.ret.insert((context.from_chain, context.id), &s);
-            s
+            self.ret.insert(&context.from_chain, context.id, &param)?;
+            Ok(ink::prelude::format!("{:?}", param))
         }
 
         /// Receives message from another chain 
         #[ink(message)]
-        pub fn get_ret(& self, key: (String, u128)) -> String {
-            self.ret.get(key).unwrap_or(String::from("No value"))
+        pub fn get_ret(& self, key: (String, u128)) -> Option<u32> {
+            self.ret.get(&chain_registry::normalize(&key.0), key.1)
         }
     }
 